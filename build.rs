@@ -13,7 +13,6 @@ fn main() {
         ("WGPUDevice", "DeviceId"),
         ("WGPUQueue", "QueueId"),
         ("WGPUBuffer", "BufferId"),
-        ("WGPUTextureView", "TextureViewId"),
         ("WGPUTexture", "TextureId"),
         ("WGPUSampler", "SamplerId"),
         ("WGPUBindGroupLayout", "BindGroupLayoutId"),
@@ -26,10 +25,13 @@ fn main() {
         ("WGPUCommandBuffer", "CommandBufferId"),
         ("WGPURenderPassEncoder", "RenderPassEncoderId"),
         ("WGPUComputePassEncoder", "ComputePassEncoderId"),
-        ("WGPURenderBundleEncoder", "ComputePipelineId"),
+        ("WGPURenderBundleEncoder", "RenderBundleEncoderId"),
         ("WGPURenderBundle", "RenderBundleId"),
         ("WGPUQuerySet", "QuerySetId"),
     ];
+    // Handles that C callers may leave NULL in structs, like a color attachment's
+    // resolve target. `Option<Id>` has the same layout, with NULL mapping to `None`.
+    let nullable_types_to_rename = vec![("WGPUTextureView", "TextureViewId")];
     let mut builder = bindgen::Builder::default()
         .header("ffi/webgpu-headers/webgpu.h")
         .header("ffi/wgpu.h")
@@ -47,6 +49,15 @@ fn main() {
             .blacklist_type(format!("{}Impl", old_name))
             .raw_line(format!("type {} = wgc::id::{};", old_name, new_name));
     }
+    for (old_name, new_name) in nullable_types_to_rename {
+        builder = builder
+            .blacklist_type(old_name)
+            .blacklist_type(format!("{}Impl", old_name))
+            .raw_line(format!(
+                "type {} = Option<wgc::id::{}>;",
+                old_name, new_name
+            ));
+    }
 
     // See https://github.com/rust-lang/rust-bindgen/issues/1780
     if let Ok("ios") = env::var("CARGO_CFG_TARGET_OS").as_ref().map(|x| &**x) {
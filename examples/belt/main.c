@@ -0,0 +1,116 @@
+#include "framework.h"
+#include "webgpu-headers/webgpu.h"
+#include "wgpu.h"
+
+#include <stdio.h>
+#include <string.h>
+
+#define CHECK(condition, message)      \
+    if (!(condition)) {                \
+        printf("%s\n", message);       \
+        return 1;                      \
+    }
+
+#define FRAMES 3
+#define WRITES 4
+#define WRITE_COUNT 4
+#define WRITE_SIZE (WRITE_COUNT * sizeof(uint32_t))
+#define TARGET_SIZE (WRITES * WRITE_SIZE)
+// Two writes per chunk, so every frame spans several chunks.
+#define CHUNK_SIZE (2 * WRITE_SIZE)
+
+int main(
+    int argc,
+    char* argv[])
+{
+    initializeLog();
+
+    WGPUAdapter adapter;
+    wgpuInstanceRequestAdapter(NULL,
+        &(WGPURequestAdapterOptions) {
+            .nextInChain = NULL,
+            .compatibleSurface = NULL,
+        },
+        request_adapter_callback,
+        (void*)&adapter);
+
+    WGPUDevice device;
+    wgpuAdapterRequestDevice(adapter,
+        &(WGPUDeviceDescriptor) {
+            .nextInChain = (const WGPUChainedStruct*)&(WGPUDeviceExtras) {
+                .chain = (WGPUChainedStruct) {
+                    .next = NULL,
+                    .sType = WGPUSType_DeviceExtras,
+                },
+                .maxBindGroups = 1,
+                .label = "Device",
+                .tracePath = NULL,
+            },
+        },
+        request_device_callback, (void*)&device);
+    WGPUQueue queue = wgpuDeviceGetQueue(device);
+
+    WGPUBuffer target = wgpuDeviceCreateBuffer(
+        device,
+        &(WGPUBufferDescriptor) {
+            .nextInChain = NULL,
+            .label = "Target Buffer",
+            .usage = WGPUBufferUsage_CopyDst | WGPUBufferUsage_CopySrc,
+            .size = TARGET_SIZE,
+            .mappedAtCreation = false,
+        });
+    WGPUBuffer readback = wgpuDeviceCreateBuffer(
+        device,
+        &(WGPUBufferDescriptor) {
+            .nextInChain = NULL,
+            .label = "Readback Buffer",
+            .usage = WGPUBufferUsage_MapRead | WGPUBufferUsage_CopyDst,
+            .size = TARGET_SIZE,
+            .mappedAtCreation = false,
+        });
+
+    WGPUStagingBelt belt = wgpuDeviceCreateStagingBelt(device, CHUNK_SIZE);
+
+    // Each frame uploads new contents through the belt, which reuses the chunks recalled
+    // in the frames before it.
+    for (uint32_t frame = 0; frame < FRAMES; frame++) {
+        WGPUCommandEncoder encoder = wgpuDeviceCreateCommandEncoder(
+            device, &(WGPUCommandEncoderDescriptor) {
+                        .label = NULL,
+                    });
+
+        for (uint32_t write = 0; write < WRITES; write++) {
+            uint32_t *data = (uint32_t *) wgpuStagingBeltWriteBuffer(
+                belt, encoder, target, write * WRITE_SIZE, WRITE_SIZE);
+            CHECK(data != NULL, "Staging belt writes must return memory to write to");
+            for (uint32_t i = 0; i < WRITE_COUNT; i++) {
+                data[i] = frame * 1000 + write * WRITE_COUNT + i;
+            }
+        }
+        wgpuCommandEncoderCopyBufferToBuffer(encoder, target, 0, readback, 0, TARGET_SIZE);
+
+        wgpuStagingBeltFinish(belt);
+        WGPUCommandBuffer cmdBuffer = wgpuCommandEncoderFinish(
+            encoder, &(WGPUCommandBufferDescriptor) { .label = NULL });
+        wgpuQueueSubmit(queue, 1, &cmdBuffer);
+        wgpuStagingBeltRecall(belt);
+
+        wgpuBufferMapAsync(readback, WGPUMapMode_Read, 0, TARGET_SIZE, readBufferMap, NULL);
+        wgpuDevicePoll(device, true);
+
+        const uint32_t *contents = (const uint32_t *) wgpuBufferGetConstMappedRange(readback, 0, TARGET_SIZE);
+        CHECK(contents != NULL, "Readback buffer must be mapped");
+        for (uint32_t i = 0; i < WRITES * WRITE_COUNT; i++) {
+            if (contents[i] != frame * 1000 + i) {
+                printf("Frame %u: value %u is %u, expected %u\n", frame, i, contents[i], frame * 1000 + i);
+                return 1;
+            }
+        }
+        wgpuBufferUnmap(readback);
+        printf("Frame %u uploaded through the staging belt\n", frame);
+    }
+
+    wgpuStagingBeltDrop(belt);
+
+    return 0;
+}
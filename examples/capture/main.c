@@ -5,6 +5,15 @@
 
 #include <stdio.h>
 
+static void saveTexture(WGPUBufferMapAsyncStatus status, const uint8_t *data, size_t dataSize, void *userdata) {
+    if (status != WGPUBufferMapAsyncStatus_Success) {
+        printf("Unable to read back texture: %d\n", status);
+        return;
+    }
+    const char *filename = "red.png";
+    save_png(filename, data, (const BufferDimensions *) userdata);
+}
+
 int main(
     int argc,
     char* argv[])
@@ -38,21 +47,9 @@ int main(
         },
         request_device_callback, (void*)&device);
 
-    BufferDimensions bufferDimensions = buffer_dimensions_new(width, height);
-    uint64_t bufferSize = bufferDimensions.padded_bytes_per_row * bufferDimensions.height;
-    WGPUBuffer outputBuffer = wgpuDeviceCreateBuffer(
-        device,
-        &(WGPUBufferDescriptor) {
-            .nextInChain = NULL,
-            .label = "Output Buffer",
-            .usage = WGPUBufferUsage_MapRead | WGPUBufferUsage_CopyDst,
-            .size = bufferSize,
-            .mappedAtCreation = false,
-        });
-
     WGPUExtent3D textureExtent = (WGPUExtent3D) {
-        .width = bufferDimensions.width,
-        .height = bufferDimensions.height,
+        .width = width,
+        .height = height,
         .depth = 1,
     };
     WGPUTexture texture = wgpuDeviceCreateTexture(device,
@@ -103,40 +100,20 @@ int main(
         });
     wgpuRenderPassEncoderEndPass(renderPass);
 
-    wgpuCommandEncoderCopyTextureToBuffer(
-            encoder,
-            &(WGPUImageCopyTexture) {
-                .texture = texture,
-                .mipLevel = 0,
-                .origin = (WGPUOrigin3D) {
-                    .x = 0,
-                    .y = 0,
-                    .z = 0,
-                },  
-            },
-            &(WGPUImageCopyBuffer) {
-                .buffer = outputBuffer,
-                .layout = (WGPUTextureDataLayout) {
-                    .offset = 0,
-                    .bytesPerRow = bufferDimensions.padded_bytes_per_row,
-                    .rowsPerImage = 0,
-                }
-            },
-            &textureExtent);
-
     WGPUQueue queue = wgpuDeviceGetQueue(device);
     WGPUCommandBuffer cmdBuffer = wgpuCommandEncoderFinish(
         encoder, &(WGPUCommandBufferDescriptor) { .label = NULL });
     wgpuQueueSubmit(queue, 1, &cmdBuffer);
 
-    wgpuBufferMapAsync(outputBuffer, WGPUMapMode_Read, 0, bufferSize, readBufferMap, NULL);
+    // The texture is read back tightly packed, without any row padding.
+    BufferDimensions bufferDimensions = (BufferDimensions) {
+        .width = width,
+        .height = height,
+        .unpadded_bytes_per_row = width * 4,
+        .padded_bytes_per_row = width * 4,
+    };
+    wgpuTextureReadAsync(texture, 0, NULL, saveTexture, &bufferDimensions);
     wgpuDevicePoll(device, true);
 
-    uint8_t *data = (uint8_t *) wgpuBufferGetMappedRange(outputBuffer, 0, bufferSize);
-    const char *filename = "red.png";
-    save_png(filename, data, &bufferDimensions);
-
-    wgpuBufferUnmap(outputBuffer);
-
     return 0;
 }
\ No newline at end of file
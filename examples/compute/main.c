@@ -125,7 +125,7 @@ int main()
     wgpuBufferMapAsync(stagingBuffer, WGPUMapMode_Read, 0, numbersSize, readBufferMap, NULL);
     wgpuDevicePoll(device, true);
 
-    uint32_t *times = (uint32_t *) wgpuBufferGetMappedRange(stagingBuffer, 0, numbersSize);
+    const uint32_t *times = (const uint32_t *) wgpuBufferGetConstMappedRange(stagingBuffer, 0, numbersSize);
 
     printf("Times: [%d, %d, %d, %d]\n",
         times[0],
@@ -0,0 +1,206 @@
+#include "framework.h"
+#include "webgpu-headers/webgpu.h"
+#include "wgpu.h"
+
+#include <stdio.h>
+#include <string.h>
+
+#define WIDTH 64
+#define HEIGHT 64
+#define CLEAR_DEPTH 0.5f
+#define FILL_BYTE 0xAB
+
+static WGPUTexture createDepthTexture(WGPUDevice device, WGPUTextureFormat format) {
+    return wgpuDeviceCreateTexture(device,
+        &(WGPUTextureDescriptor) {
+            .nextInChain = NULL,
+            .label = "Depth Texture",
+            .size = (WGPUExtent3D) {
+                .width = WIDTH,
+                .height = HEIGHT,
+                .depth = 1,
+            },
+            .mipLevelCount = 1,
+            .sampleCount = 1,
+            .dimension = WGPUTextureDimension_2D,
+            .format = format,
+            .usage = WGPUTextureUsage_RenderAttachment | WGPUTextureUsage_CopySrc,
+        });
+}
+
+static void copyToBuffer(WGPUDevice device, WGPUTexture texture, WGPUTextureAspect aspect, WGPUBuffer buffer) {
+    WGPUCommandEncoder encoder = wgpuDeviceCreateCommandEncoder(
+        device, &(WGPUCommandEncoderDescriptor) {
+                    .label = NULL,
+                });
+    wgpuCommandEncoderCopyTextureToBuffer(
+            encoder,
+            &(WGPUImageCopyTexture) {
+                .texture = texture,
+                .mipLevel = 0,
+                .origin = (WGPUOrigin3D) {
+                    .x = 0,
+                    .y = 0,
+                    .z = 0,
+                },
+                .aspect = aspect,
+            },
+            &(WGPUImageCopyBuffer) {
+                .buffer = buffer,
+                .layout = (WGPUTextureDataLayout) {
+                    .offset = 0,
+                    .bytesPerRow = WIDTH * sizeof(float),
+                    .rowsPerImage = 0,
+                }
+            },
+            &(WGPUExtent3D) {
+                .width = WIDTH,
+                .height = HEIGHT,
+                .depth = 1,
+            });
+
+    WGPUCommandBuffer cmdBuffer = wgpuCommandEncoderFinish(
+        encoder, &(WGPUCommandBufferDescriptor) { .label = NULL });
+    wgpuQueueSubmit(wgpuDeviceGetQueue(device), 1, &cmdBuffer);
+}
+
+static WGPUBuffer readDepth(WGPUDevice device, WGPUTexture texture, WGPUTextureAspect aspect, uint64_t bufferSize) {
+    WGPUBuffer buffer = wgpuDeviceCreateBuffer(
+        device,
+        &(WGPUBufferDescriptor) {
+            .nextInChain = NULL,
+            .label = "Depth Buffer",
+            .usage = WGPUBufferUsage_MapRead | WGPUBufferUsage_CopyDst,
+            .size = bufferSize,
+            .mappedAtCreation = false,
+        });
+
+    copyToBuffer(device, texture, aspect, buffer);
+
+    wgpuBufferMapAsync(buffer, WGPUMapMode_Read, 0, bufferSize, readBufferMap, NULL);
+    wgpuDevicePoll(device, true);
+    return buffer;
+}
+
+int main(
+    int argc,
+    char* argv[])
+{
+    initializeLog();
+
+    WGPUAdapter adapter;
+    wgpuInstanceRequestAdapter(NULL,
+        &(WGPURequestAdapterOptions) {
+            .nextInChain = NULL,
+            .compatibleSurface = NULL,
+        },
+        request_adapter_callback,
+        (void*)&adapter);
+
+    WGPUDevice device;
+    wgpuAdapterRequestDevice(adapter,
+        &(WGPUDeviceDescriptor) {
+            .nextInChain = (const WGPUChainedStruct*)&(WGPUDeviceExtras) {
+                .chain = (WGPUChainedStruct) {
+                    .next = NULL,
+                    .sType = WGPUSType_DeviceExtras,
+                },
+                .maxBindGroups = 1,
+                .label = "Device",
+                .tracePath = NULL,
+            },
+        },
+        request_device_callback, (void*)&device);
+
+    WGPUTexture depthTexture = createDepthTexture(device, WGPUTextureFormat_Depth32Float);
+    WGPUTextureView depthView = wgpuTextureCreateView(depthTexture, &(WGPUTextureViewDescriptor) {
+        .nextInChain = NULL,
+        .label = NULL,
+        .format = WGPUTextureFormat_Undefined,
+        .dimension = WGPUTextureViewDimension_Undefined,
+        .aspect = WGPUTextureAspect_All,
+        .arrayLayerCount = 0,
+        .baseArrayLayer = 0,
+        .baseMipLevel = 0,
+        .mipLevelCount = 0,
+    });
+
+    WGPUCommandEncoder encoder = wgpuDeviceCreateCommandEncoder(
+        device, &(WGPUCommandEncoderDescriptor) {
+                    .label = NULL,
+                });
+    WGPURenderPassEncoder renderPass = wgpuCommandEncoderBeginRenderPass(encoder,
+        &(WGPURenderPassDescriptor) {
+            .colorAttachments = NULL,
+            .colorAttachmentCount = 0,
+            .depthStencilAttachment = &(WGPURenderPassDepthStencilAttachmentDescriptor) {
+                .attachment = depthView,
+                .depthLoadOp = WGPULoadOp_Clear,
+                .depthStoreOp = WGPUStoreOp_Store,
+                .clearDepth = CLEAR_DEPTH,
+                .depthReadOnly = false,
+                .stencilLoadOp = WGPULoadOp_Load,
+                .stencilStoreOp = WGPUStoreOp_Store,
+                .clearStencil = 0,
+                .stencilReadOnly = false,
+            },
+        });
+    wgpuRenderPassEncoderEndPass(renderPass);
+    WGPUCommandBuffer cmdBuffer = wgpuCommandEncoderFinish(
+        encoder, &(WGPUCommandBufferDescriptor) { .label = NULL });
+    wgpuQueueSubmit(wgpuDeviceGetQueue(device), 1, &cmdBuffer);
+
+    // Depth32Float has a single aspect, so selecting it must copy the same data as All.
+    uint64_t bufferSize = WIDTH * HEIGHT * sizeof(float);
+    WGPUBuffer depthOnly = readDepth(device, depthTexture, WGPUTextureAspect_DepthOnly, bufferSize);
+    WGPUBuffer all = readDepth(device, depthTexture, WGPUTextureAspect_All, bufferSize);
+
+    const float *depthOnlyData = (const float *) wgpuBufferGetConstMappedRange(depthOnly, 0, bufferSize);
+    const float *allData = (const float *) wgpuBufferGetConstMappedRange(all, 0, bufferSize);
+
+    size_t mismatches = 0;
+    for (size_t i = 0; i < WIDTH * HEIGHT; i++) {
+        if (depthOnlyData[i] != CLEAR_DEPTH || allData[i] != CLEAR_DEPTH) {
+            mismatches++;
+        }
+    }
+
+    wgpuBufferUnmap(depthOnly);
+    wgpuBufferUnmap(all);
+
+    if (mismatches != 0) {
+        printf("%zu depth values differ from the clear depth\n", mismatches);
+        return 1;
+    }
+    printf("Depth-only readback matches the clear depth\n");
+
+    // Single aspects of Depth24PlusStencil8 can't be copied, so the copy is refused and leaves the buffer untouched.
+    WGPUTexture stencilTexture = createDepthTexture(device, WGPUTextureFormat_Depth24PlusStencil8);
+    WGPUBuffer stencilOnly = wgpuDeviceCreateBuffer(
+        device,
+        &(WGPUBufferDescriptor) {
+            .nextInChain = NULL,
+            .label = "Stencil Buffer",
+            .usage = WGPUBufferUsage_MapRead | WGPUBufferUsage_CopyDst,
+            .size = bufferSize,
+            .mappedAtCreation = true,
+        });
+    memset(wgpuBufferGetMappedRange(stencilOnly, 0, bufferSize), FILL_BYTE, bufferSize);
+    wgpuBufferUnmap(stencilOnly);
+
+    copyToBuffer(device, stencilTexture, WGPUTextureAspect_StencilOnly, stencilOnly);
+    wgpuBufferMapAsync(stencilOnly, WGPUMapMode_Read, 0, bufferSize, readBufferMap, NULL);
+    wgpuDevicePoll(device, true);
+
+    const uint8_t *stencilData = (const uint8_t *) wgpuBufferGetConstMappedRange(stencilOnly, 0, bufferSize);
+    for (size_t i = 0; i < bufferSize; i++) {
+        if (stencilData[i] != FILL_BYTE) {
+            printf("Refused stencil-only copy wrote to the buffer\n");
+            return 1;
+        }
+    }
+    wgpuBufferUnmap(stencilOnly);
+    printf("Stencil-only copy of Depth24PlusStencil8 was refused\n");
+
+    return 0;
+}
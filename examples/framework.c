@@ -37,7 +37,7 @@ void request_device_callback(WGPUDevice received, void* userdata)
     *(WGPUDevice*)userdata = received;
 }
 
-void readBufferMap(WGPUBufferMapAsyncStatus status, uint8_t* userdata){
+void readBufferMap(WGPUBufferMapAsyncStatus status, void* userdata){
 }
 
 void logCallback(WGPULogLevel level, const char *msg) {
@@ -6,6 +6,6 @@ void request_adapter_callback(WGPUAdapter received, void* userdata);
 
 void request_device_callback(WGPUDevice received, void* userdata);
 
-void readBufferMap(WGPUBufferMapAsyncStatus status, uint8_t* userdata);
+void readBufferMap(WGPUBufferMapAsyncStatus status, void* userdata);
 
 void initializeLog();
\ No newline at end of file
@@ -0,0 +1,84 @@
+#include "framework.h"
+#include "webgpu-headers/webgpu.h"
+#include "wgpu.h"
+
+#include <stdio.h>
+#include <string.h>
+
+#define CHECK(condition, message)      \
+    if (!(condition)) {                \
+        printf("%s\n", message);       \
+        return 1;                      \
+    }
+
+int main(
+    int argc,
+    char* argv[])
+{
+    initializeLog();
+
+    WGPUAdapter adapter;
+    wgpuInstanceRequestAdapter(NULL,
+        &(WGPURequestAdapterOptions) {
+            .nextInChain = NULL,
+            .compatibleSurface = NULL,
+        },
+        request_adapter_callback,
+        (void*)&adapter);
+
+    WGPUDevice device;
+    wgpuAdapterRequestDevice(adapter,
+        &(WGPUDeviceDescriptor) {
+            .nextInChain = (const WGPUChainedStruct*)&(WGPUDeviceExtras) {
+                .chain = (WGPUChainedStruct) {
+                    .next = NULL,
+                    .sType = WGPUSType_DeviceExtras,
+                },
+                .maxBindGroups = 1,
+                .label = "Device",
+                .tracePath = NULL,
+            },
+        },
+        request_device_callback, (void*)&device);
+
+    uint32_t numbers[] = { 1, 2, 3, 4 };
+    size_t size = sizeof(numbers);
+
+    WGPUBuffer buffer = wgpuDeviceCreateBuffer(
+        device,
+        &(WGPUBufferDescriptor) {
+            .nextInChain = NULL,
+            .label = "Mapped Buffer",
+            .usage = WGPUBufferUsage_MapRead | WGPUBufferUsage_CopyDst,
+            .size = size,
+            .mappedAtCreation = true,
+        });
+
+    // Fill the buffer through two disjoint ranges of the creation mapping.
+    void *front = wgpuBufferGetMappedRange(buffer, 0, size / 2);
+    void *back = wgpuBufferGetMappedRange(buffer, size / 2, size / 2);
+    CHECK(front != NULL && back != NULL, "Disjoint ranges of the creation mapping must be available");
+    memcpy(front, numbers, size / 2);
+    memcpy(back, (const uint8_t *) numbers + size / 2, size / 2);
+
+    CHECK(wgpuBufferGetMappedRange(buffer, 4, 8) == NULL, "Overlapping ranges must be rejected");
+    CHECK(wgpuBufferGetMappedRange(buffer, 12, 8) == NULL, "Out of bounds ranges must be rejected");
+
+    wgpuBufferUnmap(buffer);
+    CHECK(wgpuBufferGetConstMappedRange(buffer, 0, size) == NULL, "Unmapped buffers must not hand out ranges");
+
+    // Read the contents back through a regular mapping.
+    wgpuBufferMapAsync(buffer, WGPUMapMode_Read, 0, size, readBufferMap, NULL);
+    wgpuDevicePoll(device, true);
+
+    CHECK(wgpuBufferGetMappedRange(buffer, 0, size) == NULL, "Read mappings must not hand out writable ranges");
+    const uint32_t *data = (const uint32_t *) wgpuBufferGetConstMappedRange(buffer, 0, size);
+    CHECK(data != NULL, "Read mappings must hand out const ranges");
+    CHECK(memcmp(data, numbers, size) == 0, "Buffer contents differ from what was written at creation");
+
+    wgpuBufferUnmap(buffer);
+
+    printf("Map-at-creation contents survived the unmap\n");
+
+    return 0;
+}
@@ -0,0 +1,222 @@
+#include "framework.h"
+#include "helper.h"
+#include "webgpu-headers/webgpu.h"
+#include "wgpu.h"
+
+#include <stdio.h>
+
+#define SAMPLE_COUNT 4
+
+int main(
+    int argc,
+    char* argv[])
+{
+    initializeLog();
+
+    int width = 100;
+    int height = 100;
+
+    WGPUAdapter adapter;
+    wgpuInstanceRequestAdapter(NULL,
+        &(WGPURequestAdapterOptions) {
+            .nextInChain = NULL,
+            .compatibleSurface = NULL,
+        },
+        request_adapter_callback,
+        (void*)&adapter);
+
+    WGPUDevice device;
+    wgpuAdapterRequestDevice(adapter,
+        &(WGPUDeviceDescriptor) {
+            .nextInChain = (const WGPUChainedStruct*)&(WGPUDeviceExtras) {
+                .chain = (WGPUChainedStruct) {
+                    .next = NULL,
+                    .sType = WGPUSType_DeviceExtras,
+                },
+                .maxBindGroups = 1,
+                .label = "Device",
+                .tracePath = NULL,
+            },
+        },
+        request_device_callback, (void*)&device);
+
+    WGPUShaderModuleDescriptor shaderSource = load_wgsl("shader.wgsl");
+    WGPUShaderModule shader = wgpuDeviceCreateShaderModule(device, &shaderSource);
+
+    WGPUPipelineLayout pipelineLayout = wgpuDeviceCreatePipelineLayout(device,
+        &(WGPUPipelineLayoutDescriptor) {
+            .bindGroupLayouts = NULL,
+            .bindGroupLayoutCount = 0 });
+
+    WGPURenderPipeline pipeline = wgpuDeviceCreateRenderPipeline(
+        device,
+        &(WGPURenderPipelineDescriptor) {
+            .label = "Render pipeline",
+            .layout = pipelineLayout,
+            .vertex = (WGPUVertexState) {
+                .module = shader,
+                .entryPoint = "vs_main",
+                .bufferCount = 0,
+                .buffers = NULL,
+            },
+            .primitive = (WGPUPrimitiveState) {
+                .topology = WGPUPrimitiveTopology_TriangleList,
+                .stripIndexFormat = WGPUIndexFormat_Undefined,
+                .frontFace = WGPUFrontFace_CCW,
+                .cullMode = WGPUCullMode_None
+            },
+            .multisample = (WGPUMultisampleState) {
+                .count = SAMPLE_COUNT,
+                .mask = ~0,
+                .alphaToCoverageEnabled = false,
+            },
+            .fragment = &(WGPUFragmentState) {
+                .module = shader,
+                .entryPoint = "fs_main",
+                .targetCount = 1,
+                .targets = &(WGPUColorTargetState) {
+                    .format = WGPUTextureFormat_RGBA8Unorm,
+                    .blend = NULL,
+                    .writeMask = WGPUColorWriteMask_All
+                },
+            },
+            .depthStencil = NULL,
+        });
+    wgpuShaderModuleDrop(shader);
+
+    BufferDimensions bufferDimensions = buffer_dimensions_new(width, height);
+    uint64_t bufferSize = bufferDimensions.padded_bytes_per_row * bufferDimensions.height;
+    WGPUBuffer outputBuffer = wgpuDeviceCreateBuffer(
+        device,
+        &(WGPUBufferDescriptor) {
+            .nextInChain = NULL,
+            .label = "Output Buffer",
+            .usage = WGPUBufferUsage_MapRead | WGPUBufferUsage_CopyDst,
+            .size = bufferSize,
+            .mappedAtCreation = false,
+        });
+
+    WGPUExtent3D textureExtent = (WGPUExtent3D) {
+        .width = bufferDimensions.width,
+        .height = bufferDimensions.height,
+        .depth = 1,
+    };
+    WGPUTexture multisampledTexture = wgpuDeviceCreateTexture(device,
+        &(WGPUTextureDescriptor) {
+            .nextInChain = NULL,
+            .label = "Multisampled Texture",
+            .size = textureExtent,
+            .mipLevelCount = 1,
+            .sampleCount = SAMPLE_COUNT,
+            .dimension = WGPUTextureDimension_2D,
+            .format = WGPUTextureFormat_RGBA8Unorm,
+            .usage = WGPUTextureUsage_RenderAttachment,
+        });
+    WGPUTexture resolvedTexture = wgpuDeviceCreateTexture(device,
+        &(WGPUTextureDescriptor) {
+            .nextInChain = NULL,
+            .label = "Resolved Texture",
+            .size = textureExtent,
+            .mipLevelCount = 1,
+            .sampleCount = 1,
+            .dimension = WGPUTextureDimension_2D,
+            .format = WGPUTextureFormat_RGBA8Unorm,
+            .usage = WGPUTextureUsage_RenderAttachment | WGPUTextureUsage_CopySrc,
+        });
+
+    WGPUTextureViewDescriptor viewDescriptor = (WGPUTextureViewDescriptor) {
+        .nextInChain = NULL,
+        .label = NULL,
+        .format = WGPUTextureFormat_Undefined,
+        .dimension = WGPUTextureViewDimension_Undefined,
+        .aspect = WGPUTextureAspect_All,
+        .arrayLayerCount = 0,
+        .baseArrayLayer = 0,
+        .baseMipLevel = 0,
+        .mipLevelCount = 0,
+    };
+    WGPUTextureView multisampledView = wgpuTextureCreateView(multisampledTexture, &viewDescriptor);
+    WGPUTextureView resolvedView = wgpuTextureCreateView(resolvedTexture, &viewDescriptor);
+
+    WGPUCommandEncoder encoder = wgpuDeviceCreateCommandEncoder(
+        device, &(WGPUCommandEncoderDescriptor) {
+                    .label = NULL,
+                });
+
+    WGPURenderPassEncoder renderPass = wgpuCommandEncoderBeginRenderPass(encoder,
+        &(WGPURenderPassDescriptor) {
+            .colorAttachments = &(WGPURenderPassColorAttachmentDescriptor) {
+                .attachment = multisampledView,
+                .resolveTarget = resolvedView,
+                .loadOp = WGPULoadOp_Clear,
+                .storeOp = WGPUStoreOp_Store,
+                .clearColor = (WGPUColor) {
+                    .r = 0.0,
+                    .g = 0.0,
+                    .b = 0.0,
+                    .a = 1.0,
+                },
+            },
+            .colorAttachmentCount = 1,
+            .depthStencilAttachment = NULL,
+        });
+    wgpuRenderPassEncoderSetPipeline(renderPass, pipeline);
+    wgpuRenderPassEncoderDraw(renderPass, 3, 1, 0, 0);
+    wgpuRenderPassEncoderEndPass(renderPass);
+
+    wgpuCommandEncoderCopyTextureToBuffer(
+            encoder,
+            &(WGPUImageCopyTexture) {
+                .texture = resolvedTexture,
+                .mipLevel = 0,
+                .origin = (WGPUOrigin3D) {
+                    .x = 0,
+                    .y = 0,
+                    .z = 0,
+                },
+            },
+            &(WGPUImageCopyBuffer) {
+                .buffer = outputBuffer,
+                .layout = (WGPUTextureDataLayout) {
+                    .offset = 0,
+                    .bytesPerRow = bufferDimensions.padded_bytes_per_row,
+                    .rowsPerImage = 0,
+                }
+            },
+            &textureExtent);
+
+    WGPUQueue queue = wgpuDeviceGetQueue(device);
+    WGPUCommandBuffer cmdBuffer = wgpuCommandEncoderFinish(
+        encoder, &(WGPUCommandBufferDescriptor) { .label = NULL });
+    wgpuQueueSubmit(queue, 1, &cmdBuffer);
+
+    wgpuBufferMapAsync(outputBuffer, WGPUMapMode_Read, 0, bufferSize, readBufferMap, NULL);
+    wgpuDevicePoll(device, true);
+
+    const uint8_t *data = (const uint8_t *) wgpuBufferGetConstMappedRange(outputBuffer, 0, bufferSize);
+
+    // Pixels on the triangle's edges are only partially covered, so after the
+    // resolve they must end up somewhere between the clear color and the triangle.
+    size_t blendedPixels = 0;
+    for (size_t y = 0; y < bufferDimensions.height; y++) {
+        for (size_t x = 0; x < bufferDimensions.width; x++) {
+            uint8_t red = data[y * bufferDimensions.padded_bytes_per_row + x * 4];
+            if (red != 0 && red != 255) {
+                blendedPixels++;
+            }
+        }
+    }
+
+    const char *filename = "msaa.png";
+    save_png(filename, data, &bufferDimensions);
+
+    wgpuBufferUnmap(outputBuffer);
+
+    if (blendedPixels == 0) {
+        printf("Resolved image has no anti-aliased edges\n");
+        return 1;
+    }
+    printf("Resolved image has %zu anti-aliased edge pixels\n", blendedPixels);
+
+    return 0;
+}
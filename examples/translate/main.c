@@ -0,0 +1,66 @@
+#include "framework.h"
+#include "webgpu-headers/webgpu.h"
+#include "wgpu.h"
+
+#include <stdio.h>
+#include <string.h>
+
+#define CHECK(condition, message)      \
+    if (!(condition)) {                \
+        printf("%s\n", message);       \
+        return 1;                      \
+    }
+
+#define SPIRV_MAGIC 0x07230203
+
+static const char *invalidSource =
+    "fn main() {\n"
+    "    var x: u32 = 1u;\n"
+    "    x = ;\n"
+    "}\n";
+
+int main(
+    int argc,
+    char* argv[])
+{
+    initializeLog();
+
+    WGPUShaderModuleDescriptor shaderSource = load_wgsl("shader.wgsl");
+    const char *code = ((const WGPUShaderModuleWGSLDescriptor *) shaderSource.nextInChain)->source;
+
+    WGPUShaderTranslation translation = wgpuTranslateShader(
+        &(WGPUShaderTranslationSource) {
+            .code = code,
+            .codeSize = strlen(code),
+            .entryPoint = NULL,
+            .stage = WGPUShaderStage_None,
+        },
+        WGPUShaderSourceKind_WGSL,
+        WGPUShaderTargetKind_SPIRV);
+    CHECK(translation.status == WGPUShaderTranslationStatus_Success, "Valid WGSL must translate to SPIR-V");
+    CHECK(translation.message == NULL, "Successful translations must not carry a message");
+    CHECK(translation.dataSize > 0 && translation.dataSize % 4 == 0, "SPIR-V output must be a sequence of words");
+    uint32_t magic;
+    memcpy(&magic, translation.data, sizeof(magic));
+    CHECK(magic == SPIRV_MAGIC, "SPIR-V output must start with the magic number");
+    printf("Translated %zu bytes of WGSL into %zu bytes of SPIR-V\n", strlen(code), translation.dataSize);
+    wgpuShaderTranslationFree(translation);
+
+    translation = wgpuTranslateShader(
+        &(WGPUShaderTranslationSource) {
+            .code = invalidSource,
+            .codeSize = strlen(invalidSource),
+            .entryPoint = NULL,
+            .stage = WGPUShaderStage_None,
+        },
+        WGPUShaderSourceKind_WGSL,
+        WGPUShaderTargetKind_SPIRV);
+    CHECK(translation.status == WGPUShaderTranslationStatus_ParseError, "Invalid WGSL must fail to parse");
+    CHECK(translation.data == NULL && translation.dataSize == 0, "Failed translations must not carry data");
+    CHECK(translation.message != NULL, "Failed translations must carry a message");
+    CHECK(translation.line == 3 && translation.column > 0, "Parse errors must point at the offending line");
+    printf("Invalid WGSL rejected at %u:%u: %s\n", translation.line, translation.column, translation.message);
+    wgpuShaderTranslationFree(translation);
+
+    return 0;
+}
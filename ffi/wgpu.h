@@ -6,17 +6,93 @@
 typedef enum WGPUNativeSType {
     // Start at 6 to prevent collisions with webgpu STypes
     WGPUSType_DeviceExtras = 0x60000001,
+    WGPUSType_ProgrammableStageExtras = 0x60000002,
     WGPUNativeSType_Force32 = 0x7FFFFFFF
 } WGPUNativeSType;
 
 
+typedef enum WGPUNativeFeature {
+    WGPUNativeFeature_MultiDrawIndirect = 0x00000001,
+    WGPUNativeFeature_MultiDrawIndirectCount = 0x00000002,
+    WGPUNativeFeature_TimestampQuery = 0x00000004,
+    WGPUNativeFeature_PipelineStatisticsQuery = 0x00000008,
+    WGPUNativeFeature_TextureCompressionBc = 0x00000010,
+    WGPUNativeFeature_TextureCompressionEtc2 = 0x00000020,
+    WGPUNativeFeature_TextureCompressionAstcLdr = 0x00000040,
+    WGPUNativeFeature_Force32 = 0x7FFFFFFF
+} WGPUNativeFeature;
+typedef uint32_t WGPUNativeFeatureFlags;
+
+// Texture formats that webgpu.h doesn't have yet, usable wherever a WGPUTextureFormat is expected.
+// ETC2/EAC formats require WGPUNativeFeature_TextureCompressionEtc2, ASTC formats require
+// WGPUNativeFeature_TextureCompressionAstcLdr.
+typedef enum WGPUNativeTextureFormat {
+    // Placed in the same range as the native STypes to prevent collisions with webgpu texture formats
+    WGPUTextureFormat_ETC2RGB8Unorm = 0x60000001,
+    WGPUTextureFormat_ETC2RGB8UnormSrgb = 0x60000002,
+    WGPUTextureFormat_ETC2RGB8A1Unorm = 0x60000003,
+    WGPUTextureFormat_ETC2RGB8A1UnormSrgb = 0x60000004,
+    WGPUTextureFormat_ETC2RGBA8Unorm = 0x60000005,
+    WGPUTextureFormat_ETC2RGBA8UnormSrgb = 0x60000006,
+    WGPUTextureFormat_EACR11Unorm = 0x60000007,
+    WGPUTextureFormat_EACR11Snorm = 0x60000008,
+    WGPUTextureFormat_EACRG11Unorm = 0x60000009,
+    WGPUTextureFormat_EACRG11Snorm = 0x6000000A,
+    WGPUTextureFormat_ASTC4x4Unorm = 0x6000000B,
+    WGPUTextureFormat_ASTC4x4UnormSrgb = 0x6000000C,
+    WGPUTextureFormat_ASTC5x4Unorm = 0x6000000D,
+    WGPUTextureFormat_ASTC5x4UnormSrgb = 0x6000000E,
+    WGPUTextureFormat_ASTC5x5Unorm = 0x6000000F,
+    WGPUTextureFormat_ASTC5x5UnormSrgb = 0x60000010,
+    WGPUTextureFormat_ASTC6x5Unorm = 0x60000011,
+    WGPUTextureFormat_ASTC6x5UnormSrgb = 0x60000012,
+    WGPUTextureFormat_ASTC6x6Unorm = 0x60000013,
+    WGPUTextureFormat_ASTC6x6UnormSrgb = 0x60000014,
+    WGPUTextureFormat_ASTC8x5Unorm = 0x60000015,
+    WGPUTextureFormat_ASTC8x5UnormSrgb = 0x60000016,
+    WGPUTextureFormat_ASTC8x6Unorm = 0x60000017,
+    WGPUTextureFormat_ASTC8x6UnormSrgb = 0x60000018,
+    WGPUTextureFormat_ASTC8x8Unorm = 0x60000019,
+    WGPUTextureFormat_ASTC8x8UnormSrgb = 0x6000001A,
+    WGPUTextureFormat_ASTC10x5Unorm = 0x6000001B,
+    WGPUTextureFormat_ASTC10x5UnormSrgb = 0x6000001C,
+    WGPUTextureFormat_ASTC10x6Unorm = 0x6000001D,
+    WGPUTextureFormat_ASTC10x6UnormSrgb = 0x6000001E,
+    WGPUTextureFormat_ASTC10x8Unorm = 0x6000001F,
+    WGPUTextureFormat_ASTC10x8UnormSrgb = 0x60000020,
+    WGPUTextureFormat_ASTC10x10Unorm = 0x60000021,
+    WGPUTextureFormat_ASTC10x10UnormSrgb = 0x60000022,
+    WGPUTextureFormat_ASTC12x10Unorm = 0x60000023,
+    WGPUTextureFormat_ASTC12x10UnormSrgb = 0x60000024,
+    WGPUTextureFormat_ASTC12x12Unorm = 0x60000025,
+    WGPUTextureFormat_ASTC12x12UnormSrgb = 0x60000026,
+    WGPUNativeTextureFormat_Force32 = 0x7FFFFFFF
+} WGPUNativeTextureFormat;
+
 typedef struct WGPUDeviceExtras {
     WGPUChainedStruct chain;
     uint32_t maxBindGroups;
     const char* label;
     const char* tracePath;
+    WGPUNativeFeatureFlags nativeFeatures;
 } WGPUDeviceExtras;
 
+typedef struct WGPUConstantEntry {
+    // Name of the constant, or a decimal specialization ID. IDs only match constants whose ID the shader
+    // frontend records, which the SPIR-V frontend doesn't do yet, so stripped SPIR-V can't be specialized.
+    const char* key;
+    double value;
+} WGPUConstantEntry;
+
+// Chained to the compute stage, vertex state or fragment state of a pipeline descriptor.
+// Constants are matched against the scalar module-scope constants of the shader. Workgroup sizes
+// can't be overridden. Unknown keys are reported like other pipeline creation errors.
+typedef struct WGPUProgrammableStageExtras {
+    WGPUChainedStruct chain;
+    uint32_t constantCount;
+    const WGPUConstantEntry* constants;
+} WGPUProgrammableStageExtras;
+
 typedef enum WGPULogLevel {
     WGPULogLevel_Off = 0x00000000,
     WGPULogLevel_Error = 0x00000001,
@@ -27,6 +103,86 @@ typedef enum WGPULogLevel {
     WGPULogLevel_Force32 = 0x7FFFFFFF
 } WGPULogLevel;
 
+typedef enum WGPUShaderSourceKind {
+    WGPUShaderSourceKind_WGSL = 0x00000000,
+    WGPUShaderSourceKind_SPIRV = 0x00000001,
+    WGPUShaderSourceKind_GLSL = 0x00000002,
+    WGPUShaderSourceKind_Force32 = 0x7FFFFFFF
+} WGPUShaderSourceKind;
+
+typedef enum WGPUShaderTargetKind {
+    WGPUShaderTargetKind_SPIRV = 0x00000000,
+    WGPUShaderTargetKind_GLSL = 0x00000001,
+    WGPUShaderTargetKind_WGSL = 0x00000002,
+    WGPUShaderTargetKind_Force32 = 0x7FFFFFFF
+} WGPUShaderTargetKind;
+
+typedef enum WGPUShaderTranslationStatus {
+    WGPUShaderTranslationStatus_Success = 0x00000000,
+    WGPUShaderTranslationStatus_ParseError = 0x00000001,
+    WGPUShaderTranslationStatus_ValidationError = 0x00000002,
+    WGPUShaderTranslationStatus_TranslationError = 0x00000003,
+    WGPUShaderTranslationStatus_Unsupported = 0x00000004,
+    WGPUShaderTranslationStatus_Force32 = 0x7FFFFFFF
+} WGPUShaderTranslationStatus;
+
+typedef struct WGPUShaderTranslationSource {
+    const void* code;
+    size_t codeSize;
+    // Required for GLSL input and output
+    const char* entryPoint;
+    WGPUShaderStage stage;
+} WGPUShaderTranslationSource;
+
+typedef struct WGPUShaderTranslation {
+    WGPUShaderTranslationStatus status;
+    // SPIR-V words or shader text, not null-terminated
+    const uint8_t* data;
+    size_t dataSize;
+    // Set when status is not WGPUShaderTranslationStatus_Success
+    const char* message;
+    // 1-based line and byte column of the error in the source, 0 if unknown. Only reported for WGSL input.
+    uint32_t line;
+    uint32_t column;
+} WGPUShaderTranslation;
+
+typedef uint64_t WGPUSubmissionIndex;
+
+typedef struct WGPUImageSubresourceRange {
+    WGPUTextureAspect aspect;
+    uint32_t baseMipLevel;
+    // 0 selects all remaining mip levels
+    uint32_t mipLevelCount;
+    uint32_t baseArrayLayer;
+    // 0 selects all remaining array layers
+    uint32_t arrayLayerCount;
+} WGPUImageSubresourceRange;
+
+typedef struct WGPUBlitTexture {
+    WGPUTexture texture;
+    uint32_t mipLevel;
+    // z selects the array layer
+    WGPUOrigin3D origin;
+    // depth must be 1
+    WGPUExtent3D size;
+} WGPUBlitTexture;
+
+typedef struct WGPUStagingBeltImpl* WGPUStagingBelt;
+
+typedef struct WGPUTextureRegion {
+    // z selects the first array layer of 2D textures
+    WGPUOrigin3D origin;
+    // depth selects the number of array layers of 2D textures
+    WGPUExtent3D size;
+} WGPUTextureRegion;
+
+// data is tightly packed and only valid during the callback
+typedef void (*WGPUTextureReadCallback)(WGPUBufferMapAsyncStatus status, const uint8_t* data, size_t dataSize, void* userdata);
+
+// The aspect of a WGPUImageCopyTexture may only be WGPUTextureAspect_DepthOnly or WGPUTextureAspect_StencilOnly if it
+// is the only aspect of the texture format. Single aspects of Depth24PlusStencil8 textures can't be copied yet, such
+// copies are logged as errors and skipped.
+
 typedef void (*WGPULogCallback)(WGPULogLevel level, const char *msg);
 
 void wgpuDevicePoll(WGPUDevice device, bool force_wait);
@@ -35,6 +191,86 @@ void wgpuSetLogCallback(WGPULogCallback callback);
 
 void wgpuSetLogLevel(WGPULogLevel level);
 
+WGPUShaderTranslation wgpuTranslateShader(const WGPUShaderTranslationSource* source, WGPUShaderSourceKind sourceKind, WGPUShaderTargetKind targetKind);
+
+void wgpuShaderTranslationFree(WGPUShaderTranslation translation);
+
+// Also releases the copy of the module's source kept for pipeline constant overrides
+void wgpuShaderModuleDrop(WGPUShaderModule module);
+
+void wgpuBufferDrop(WGPUBuffer buffer);
+
+void wgpuTextureDrop(WGPUTexture texture);
+
+// Also releases the internal resources kept for clears, blits and submission tracking
+void wgpuDeviceDrop(WGPUDevice device);
+
 void wgpuRenderPassEncoderSetPushConstants(WGPURenderPassEncoder encoder, WGPUShaderStage stages, uint32_t offset, uint32_t sizeBytes, void* const data);
 
+void wgpuRenderPassEncoderMultiDrawIndirect(WGPURenderPassEncoder encoder, WGPUBuffer buffer, uint64_t offset, uint32_t count);
+
+void wgpuRenderPassEncoderMultiDrawIndexedIndirect(WGPURenderPassEncoder encoder, WGPUBuffer buffer, uint64_t offset, uint32_t count);
+
+void wgpuRenderPassEncoderMultiDrawIndirectCount(WGPURenderPassEncoder encoder, WGPUBuffer buffer, uint64_t offset, WGPUBuffer count_buffer, uint64_t count_buffer_offset, uint32_t max_count);
+
+void wgpuRenderPassEncoderMultiDrawIndexedIndirectCount(WGPURenderPassEncoder encoder, WGPUBuffer buffer, uint64_t offset, WGPUBuffer count_buffer, uint64_t count_buffer_offset, uint32_t max_count);
+
+void wgpuRenderPassEncoderBeginPipelineStatisticsQuery(WGPURenderPassEncoder encoder, WGPUQuerySet querySet, uint32_t queryIndex);
+
+void wgpuRenderPassEncoderEndPipelineStatisticsQuery(WGPURenderPassEncoder encoder);
+
+void wgpuComputePassEncoderBeginPipelineStatisticsQuery(WGPUComputePassEncoder encoder, WGPUQuerySet querySet, uint32_t queryIndex);
+
+void wgpuComputePassEncoderEndPipelineStatisticsQuery(WGPUComputePassEncoder encoder);
+
+float wgpuQueueGetTimestampPeriod(WGPUQueue queue);
+
+WGPUSubmissionIndex wgpuQueueSubmitForIndex(WGPUQueue queue, uint32_t commandCount, const WGPUCommandBuffer* commands);
+
+// Returns true if the queue is idle once the submission is done or the timeout is reached. Submissions made through
+// wgpuQueueSubmit count towards idleness too, and are reported done once the GPU completed them.
+bool wgpuDevicePollUntil(WGPUDevice device, WGPUSubmissionIndex index, uint32_t timeoutMs);
+
+void wgpuCommandEncoderClearBuffer(WGPUCommandEncoder encoder, WGPUBuffer buffer, uint64_t offset, uint64_t size);
+
+void wgpuCommandEncoderClearTexture(WGPUCommandEncoder encoder, WGPUTexture texture, const WGPUImageSubresourceRange* range);
+
+// Fills mipCount mip levels after baseMipLevel, each downsampled from the one before it. A mipCount of 0 fills all remaining levels.
+void wgpuCommandEncoderGenerateMipmaps(WGPUCommandEncoder encoder, WGPUTexture texture, uint32_t baseMipLevel, uint32_t mipCount);
+
+void wgpuCommandEncoderBlitTexture(WGPUCommandEncoder encoder, const WGPUBlitTexture* source, const WGPUBlitTexture* destination, WGPUFilterMode filter);
+
+// Same as wgpuBufferMapAsync with 64-bit offsets. Invalid requests, such as invalid modes or ranges, or buffers with a
+// mapping still pending, are reported to the callback with WGPUBufferMapAsyncStatus_Error. A mapping aborted by
+// wgpuBufferUnmap stays pending until the device is polled.
+void wgpuBufferMapAsync64(WGPUBuffer buffer, WGPUMapModeFlags mode, uint64_t offset, uint64_t size, WGPUBufferMapCallback callback, void * userdata);
+
+// chunkSize is the size of the staging buffers uploads are sub-allocated from, a non-zero multiple of 4
+WGPUStagingBelt wgpuDeviceCreateStagingBelt(WGPUDevice device, uint64_t chunkSize);
+
+// Records a copy of size bytes into target at offset, and returns the memory to write them to.
+// The memory stays valid until wgpuStagingBeltFinish. size must be a multiple of 4.
+void* wgpuStagingBeltWriteBuffer(WGPUStagingBelt belt, WGPUCommandEncoder encoder, WGPUBuffer target, uint64_t offset, uint64_t size);
+
+// Call before submitting the encoders used with wgpuStagingBeltWriteBuffer
+void wgpuStagingBeltFinish(WGPUStagingBelt belt);
+
+// Call after submitting the encoders used with wgpuStagingBeltWriteBuffer. Chunks are reused once the device
+// is polled after their submissions completed.
+void wgpuStagingBeltRecall(WGPUStagingBelt belt);
+
+void wgpuStagingBeltDrop(WGPUStagingBelt belt);
+
+// The buffer is as large as the data, padded with zeroes to a multiple of 4. The descriptor's size and mappedAtCreation are ignored.
+WGPUBuffer wgpuDeviceCreateBufferInit(WGPUDevice device, const WGPUBufferDescriptor* descriptor, const void* data, size_t dataSize);
+
+// The data holds each array layer in order, each with all its mip levels tightly packed. The usage must include WGPUTextureUsage_CopyDst.
+WGPUTexture wgpuDeviceCreateTextureWithData(WGPUDevice device, const WGPUTextureDescriptor* descriptor, const void* data, size_t dataSize);
+
+// Submits a copy of the region, or the whole mip level if region is NULL. The callback is invoked once the device is
+// polled after the copy is done. The texture must have WGPUTextureUsage_CopySrc and an uncompressed format other
+// than Depth24Plus and Depth24PlusStencil8. Invalid reads, such as an out of range mipLevel or region, are reported
+// to the callback with WGPUBufferMapAsyncStatus_Error and no data.
+void wgpuTextureReadAsync(WGPUTexture texture, uint32_t mipLevel, const WGPUTextureRegion* region, WGPUTextureReadCallback callback, void* userdata);
+
 #endif 
\ No newline at end of file
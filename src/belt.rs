@@ -0,0 +1,210 @@
+use crate::{check_error, GLOBAL};
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    num::NonZeroU64,
+    sync::{Arc, Mutex, Weak},
+};
+use wgc::{gfx_select, id};
+
+/// Staging buffer the belt sub-allocates uploads from.
+struct Chunk {
+    buffer: id::BufferId,
+    size: wgt::BufferAddress,
+    offset: wgt::BufferAddress,
+}
+
+impl Chunk {
+    fn fits(&self, size: wgt::BufferAddress) -> bool {
+        self.offset + size <= self.size
+    }
+}
+
+/// Uploads data through a ring of mapped staging buffers, avoiding the allocation
+/// `wgpuQueueWriteBuffer` makes for every write.
+///
+/// Each frame goes as follows:
+/// - Write to buffers with `wgpuStagingBeltWriteBuffer`.
+/// - Call `wgpuStagingBeltFinish`.
+/// - Submit all command encoders used with `wgpuStagingBeltWriteBuffer`.
+/// - Call `wgpuStagingBeltRecall`. Chunks become reusable once the device is polled
+///   after their submission completed.
+pub struct StagingBelt {
+    device: id::DeviceId,
+    chunk_size: wgt::BufferAddress,
+    /// Chunks with mapped memory that uploads are written to at this moment.
+    active_chunks: Vec<Chunk>,
+    /// Chunks with scheduled copies that haven't been recalled yet.
+    closed_chunks: Vec<Chunk>,
+    /// Chunks that are back from the GPU and mapped again, ready to be used.
+    free_chunks: Arc<Mutex<Vec<Chunk>>>,
+}
+
+/// User data of the mapping that returns a chunk to its belt.
+struct RecalledChunk {
+    chunk: Chunk,
+    /// Weak, as the belt may be dropped while chunks are in flight.
+    free_chunks: Weak<Mutex<Vec<Chunk>>>,
+}
+
+/// Creates a staging belt. `chunk_size` is the unit of internal buffer allocation.
+/// It's better when it's big, but ideally still 1-4 times less than the total amount
+/// of data uploaded per submission.
+#[no_mangle]
+pub extern "C" fn wgpuDeviceCreateStagingBelt(
+    device: id::DeviceId,
+    chunk_size: u64,
+) -> *mut StagingBelt {
+    if chunk_size == 0 || chunk_size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+        panic!(
+            "Staging belt chunk size must be a non-zero multiple of {}",
+            wgt::COPY_BUFFER_ALIGNMENT
+        );
+    }
+    Box::into_raw(Box::new(StagingBelt {
+        device,
+        chunk_size,
+        active_chunks: Vec::new(),
+        closed_chunks: Vec::new(),
+        free_chunks: Arc::new(Mutex::new(Vec::new())),
+    }))
+}
+
+/// Allocates `size` bytes to be uploaded into `target` at `offset`, and returns the
+/// memory to write them to. It stays valid until `wgpuStagingBeltFinish`.
+///
+/// The copy is recorded into `command_encoder`, which must be submitted after
+/// `wgpuStagingBeltFinish` and before `wgpuStagingBeltRecall`.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuStagingBeltWriteBuffer(
+    belt: &mut StagingBelt,
+    command_encoder: id::CommandEncoderId,
+    target: id::BufferId,
+    offset: u64,
+    size: u64,
+) -> *mut u8 {
+    if size == 0 || size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
+        panic!(
+            "Staging belt write size must be a non-zero multiple of {}",
+            wgt::COPY_BUFFER_ALIGNMENT
+        );
+    }
+
+    let mut chunk =
+        if let Some(index) = belt.active_chunks.iter().position(|chunk| chunk.fits(size)) {
+            belt.active_chunks.swap_remove(index)
+        } else {
+            let mut free_chunks = belt.free_chunks.lock().unwrap();
+            match free_chunks.iter().position(|chunk| chunk.fits(size)) {
+                Some(index) => free_chunks.swap_remove(index),
+                None => create_chunk(belt.device, belt.chunk_size.max(size)),
+            }
+        };
+
+    gfx_select!(command_encoder => GLOBAL.command_encoder_copy_buffer_to_buffer(
+        command_encoder,
+        chunk.buffer,
+        chunk.offset,
+        target,
+        offset,
+        size))
+    .expect("Unable to copy staging belt chunk to buffer");
+
+    let data = gfx_select!(chunk.buffer => GLOBAL.buffer_get_mapped_range(chunk.buffer, chunk.offset, NonZeroU64::new(size)))
+        .expect("Unable to get mapped range")
+        .0;
+    chunk.offset += size;
+    belt.active_chunks.push(chunk);
+    data
+}
+
+/// Unmaps the chunks written to since the last call, ahead of submitting the copies
+/// reading from them.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuStagingBeltFinish(belt: &mut StagingBelt) {
+    for chunk in belt.active_chunks.drain(..) {
+        gfx_select!(chunk.buffer => GLOBAL.buffer_unmap(chunk.buffer))
+            .expect("Unable to unmap staging belt chunk");
+        belt.closed_chunks.push(chunk);
+    }
+}
+
+/// Maps the finished chunks again, which returns them to the belt once the submissions
+/// copying from them are done. Must be called after those submissions.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuStagingBeltRecall(belt: &mut StagingBelt) {
+    for chunk in belt.closed_chunks.drain(..) {
+        let buffer = chunk.buffer;
+        let size = chunk.size;
+        let recalled = Box::into_raw(Box::new(RecalledChunk {
+            chunk,
+            free_chunks: Arc::downgrade(&belt.free_chunks),
+        }));
+        let operation = wgc::resource::BufferMapOperation {
+            host: wgc::device::HostMap::Write,
+            callback: chunk_recalled,
+            user_data: recalled as *mut u8,
+        };
+        if let Err(err) = gfx_select!(buffer => GLOBAL.buffer_map_async(buffer, 0..size, operation))
+        {
+            drop(Box::from_raw(recalled));
+            panic!("Unable to recall staging belt chunk: {:?}", err);
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuStagingBeltDrop(belt: *mut StagingBelt) {
+    let belt = *Box::from_raw(belt);
+    let free_chunks = belt
+        .free_chunks
+        .lock()
+        .unwrap()
+        .drain(..)
+        .collect::<Vec<_>>();
+    for chunk in belt
+        .active_chunks
+        .into_iter()
+        .chain(belt.closed_chunks)
+        .chain(free_chunks)
+    {
+        gfx_select!(chunk.buffer => GLOBAL.buffer_drop(chunk.buffer, false));
+    }
+}
+
+unsafe fn create_chunk(device: id::DeviceId, size: wgt::BufferAddress) -> Chunk {
+    let buffer = check_error(gfx_select!(device => GLOBAL.device_create_buffer(
+        device,
+        &wgt::BufferDescriptor {
+            label: Some(Cow::Borrowed("staging belt chunk")),
+            size,
+            usage: wgt::BufferUsage::MAP_WRITE | wgt::BufferUsage::COPY_SRC,
+            mapped_at_creation: true,
+        },
+        PhantomData
+    )));
+    Chunk {
+        buffer,
+        size,
+        offset: 0,
+    }
+}
+
+unsafe extern "C" fn chunk_recalled(
+    status: wgc::resource::BufferMapAsyncStatus,
+    user_data: *mut u8,
+) {
+    let RecalledChunk {
+        mut chunk,
+        free_chunks,
+    } = *Box::from_raw(user_data as *mut RecalledChunk);
+    match (status, free_chunks.upgrade()) {
+        (wgc::resource::BufferMapAsyncStatus::Success, Some(free_chunks)) => {
+            chunk.offset = 0;
+            free_chunks.lock().unwrap().push(chunk);
+        }
+        _ => {
+            gfx_select!(chunk.buffer => GLOBAL.buffer_drop(chunk.buffer, false));
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use crate::{check_error, native, GLOBAL};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    marker::PhantomData,
+    num::{NonZeroU32, NonZeroU64},
+    ptr,
+    sync::Mutex,
+};
+use wgc::{command::render_ffi, gfx_select, id, pipeline::ShaderModuleSource};
+
+lazy_static::lazy_static! {
+    static ref BLIT_RESOURCES: Mutex<HashMap<id::DeviceId, BlitResources>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Drops the blit objects of a device that is being dropped.
+pub(crate) fn release_blit_resources(device: id::DeviceId) {
+    let resources = match BLIT_RESOURCES.lock().unwrap().remove(&device) {
+        Some(resources) => resources,
+        None => return,
+    };
+    for (_, pipeline) in resources.pipelines {
+        gfx_select!(pipeline => GLOBAL.render_pipeline_drop(pipeline));
+    }
+    gfx_select!(resources.full_rect => GLOBAL.buffer_drop(resources.full_rect, false));
+    gfx_select!(resources.linear_sampler => GLOBAL.sampler_drop(resources.linear_sampler));
+    gfx_select!(resources.nearest_sampler => GLOBAL.sampler_drop(resources.nearest_sampler));
+    gfx_select!(resources.pipeline_layout => GLOBAL.pipeline_layout_drop(resources.pipeline_layout));
+    gfx_select!(resources.bind_group_layout => GLOBAL.bind_group_layout_drop(resources.bind_group_layout));
+    gfx_select!(resources.shader => GLOBAL.shader_module_drop(resources.shader));
+}
+
+/// Objects backing the native blit commands of one device. Pipelines are created on
+/// first use of a target format and kept for the lifetime of the device.
+struct BlitResources {
+    shader: id::ShaderModuleId,
+    bind_group_layout: id::BindGroupLayoutId,
+    pipeline_layout: id::PipelineLayoutId,
+    linear_sampler: id::SamplerId,
+    nearest_sampler: id::SamplerId,
+    /// Source rectangle covering the whole source texture.
+    full_rect: id::BufferId,
+    pipelines: HashMap<wgt::TextureFormat, id::RenderPipelineId>,
+}
+
+impl BlitResources {
+    fn new(device: id::DeviceId) -> Self {
+        let shader = check_error(gfx_select!(device => GLOBAL.device_create_shader_module(
+            device,
+            &wgc::pipeline::ShaderModuleDescriptor {
+                label: Some(Cow::Borrowed("blit shader")),
+                flags: wgt::ShaderFlags::VALIDATION,
+            },
+            ShaderModuleSource::Wgsl(Cow::Borrowed(include_str!("blit.wgsl"))),
+            PhantomData
+        )));
+
+        let entries = [
+            wgt::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgt::ShaderStage::FRAGMENT,
+                ty: wgt::BindingType::Texture {
+                    sample_type: wgt::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgt::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgt::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgt::ShaderStage::FRAGMENT,
+                ty: wgt::BindingType::Sampler {
+                    filtering: true,
+                    comparison: false,
+                },
+                count: None,
+            },
+            wgt::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgt::ShaderStage::FRAGMENT,
+                ty: wgt::BindingType::Buffer {
+                    ty: wgt::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: NonZeroU64::new(RECT_SIZE),
+                },
+                count: None,
+            },
+        ];
+        let bind_group_layout = check_error(
+            gfx_select!(device => GLOBAL.device_create_bind_group_layout(
+                device,
+                &wgc::binding_model::BindGroupLayoutDescriptor {
+                    label: Some(Cow::Borrowed("blit bind group layout")),
+                    entries: Cow::Borrowed(&entries),
+                },
+                PhantomData
+            )),
+        );
+
+        let pipeline_layout =
+            check_error(gfx_select!(device => GLOBAL.device_create_pipeline_layout(
+                device,
+                &wgc::binding_model::PipelineLayoutDescriptor {
+                    label: Some(Cow::Borrowed("blit pipeline layout")),
+                    bind_group_layouts: Cow::Borrowed(&[bind_group_layout]),
+                    push_constant_ranges: Cow::Borrowed(&[]),
+                },
+                PhantomData
+            )));
+
+        let create_sampler = |filter| {
+            check_error(gfx_select!(device => GLOBAL.device_create_sampler(
+                device,
+                &wgc::resource::SamplerDescriptor {
+                    label: Some(Cow::Borrowed("blit sampler")),
+                    mag_filter: filter,
+                    min_filter: filter,
+                    ..Default::default()
+                },
+                PhantomData
+            )))
+        };
+
+        Self {
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            linear_sampler: create_sampler(wgt::FilterMode::Linear),
+            nearest_sampler: create_sampler(wgt::FilterMode::Nearest),
+            full_rect: create_rect_buffer(device, [0.0, 0.0, 1.0, 1.0]),
+            pipelines: HashMap::new(),
+        }
+    }
+
+    fn sampler(&self, filter: wgt::FilterMode) -> id::SamplerId {
+        match filter {
+            wgt::FilterMode::Linear => self.linear_sampler,
+            wgt::FilterMode::Nearest => self.nearest_sampler,
+        }
+    }
+
+    fn pipeline(
+        &mut self,
+        device: id::DeviceId,
+        format: wgt::TextureFormat,
+    ) -> id::RenderPipelineId {
+        let shader = self.shader;
+        let layout = self.pipeline_layout;
+        *self.pipelines.entry(format).or_insert_with(|| {
+            let targets = [wgt::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgt::ColorWrite::ALL,
+            }];
+            let desc = wgc::pipeline::RenderPipelineDescriptor {
+                label: Some(Cow::Borrowed("blit pipeline")),
+                layout: Some(layout),
+                vertex: wgc::pipeline::VertexState {
+                    stage: wgc::pipeline::ProgrammableStageDescriptor {
+                        module: shader,
+                        entry_point: Cow::Borrowed("vs_main"),
+                    },
+                    buffers: Cow::Borrowed(&[]),
+                },
+                primitive: wgt::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgt::MultisampleState::default(),
+                fragment: Some(wgc::pipeline::FragmentState {
+                    stage: wgc::pipeline::ProgrammableStageDescriptor {
+                        module: shader,
+                        entry_point: Cow::Borrowed("fs_main"),
+                    },
+                    targets: Cow::Borrowed(&targets),
+                }),
+            };
+            let (id, _, error) = gfx_select!(device => GLOBAL.device_create_render_pipeline(device, &desc, PhantomData, None));
+            if let Some(err) = error {
+                panic!("{:?}", err);
+            }
+            id
+        })
+    }
+}
+
+/// Fills the `mip_level_count` mip levels following `base_mip_level` of every array
+/// layer, each one downsampled from the level before it.
+///
+/// A `mip_level_count` of 0 fills all remaining mip levels.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuCommandEncoderGenerateMipmaps(
+    command_encoder: id::CommandEncoderId,
+    texture: id::TextureId,
+    base_mip_level: u32,
+    mip_level_count: u32,
+) {
+    let info = match crate::device::texture_info(texture) {
+        Some(info) => info,
+        None => return,
+    };
+    let desc = &info.desc;
+    if desc.dimension != wgt::TextureDimension::D2 || desc.sample_count != 1 {
+        panic!("Mipmaps can only be generated for single-sampled 2D textures");
+    }
+    if !desc
+        .usage
+        .contains(wgt::TextureUsage::SAMPLED | wgt::TextureUsage::RENDER_ATTACHMENT)
+    {
+        panic!("Generating mipmaps requires SAMPLED and RENDER_ATTACHMENT usage");
+    }
+    if !is_blit_source(desc.format) || !is_blit_target(desc.format) {
+        panic!(
+            "Mipmaps cannot be generated for format {:?}, it must be a filterable and renderable color format",
+            desc.format
+        );
+    }
+
+    let remaining = base_mip_level
+        .checked_add(1)
+        .and_then(|first| desc.mip_level_count.checked_sub(first))
+        .expect("Base mip level is out of bounds of the texture");
+    let mip_level_count = match mip_level_count {
+        0 => remaining,
+        count if count <= remaining => count,
+        _ => panic!("Mip level range is out of bounds of the texture"),
+    };
+
+    let mut resources = BLIT_RESOURCES.lock().unwrap();
+    let resources = resources
+        .entry(info.device)
+        .or_insert_with(|| BlitResources::new(info.device));
+    let pipeline = resources.pipeline(info.device, desc.format);
+
+    for array_layer in 0..info.array_layer_count() {
+        for mip_level in base_mip_level + 1..=base_mip_level + mip_level_count {
+            let size = info.mip_level_size(mip_level);
+            let blit = Blit {
+                source: create_view(texture, mip_level - 1, array_layer),
+                source_rect: resources.full_rect,
+                sampler: resources.linear_sampler,
+                target: create_view(texture, mip_level, array_layer),
+                viewport: [0.0, 0.0, size.width as f32, size.height as f32],
+            };
+            blit.record(command_encoder, info.device, resources, pipeline);
+        }
+    }
+}
+
+/// Copies a region of one texture to a region of another, scaling it with the given
+/// filter and converting between formats.
+///
+/// The `z` coordinate of each region's origin selects the array layer.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuCommandEncoderBlitTexture(
+    command_encoder: id::CommandEncoderId,
+    source: &native::WGPUBlitTexture,
+    destination: &native::WGPUBlitTexture,
+    filter: native::WGPUFilterMode,
+) {
+    let (source_info, destination_info) = match (
+        crate::device::texture_info(source.texture),
+        crate::device::texture_info(destination.texture),
+    ) {
+        (Some(source_info), Some(destination_info)) => (source_info, destination_info),
+        _ => return,
+    };
+    if !source_info.desc.usage.contains(wgt::TextureUsage::SAMPLED) {
+        panic!("Blit source requires SAMPLED usage");
+    }
+    if !is_blit_source(source_info.desc.format) {
+        panic!(
+            "Blit source format {:?} must be a filterable color format",
+            source_info.desc.format
+        );
+    }
+    if !destination_info
+        .desc
+        .usage
+        .contains(wgt::TextureUsage::RENDER_ATTACHMENT)
+    {
+        panic!("Blit destination requires RENDER_ATTACHMENT usage");
+    }
+    if !is_blit_target(destination_info.desc.format) {
+        panic!(
+            "Blit destination format {:?} must be a renderable float color format",
+            destination_info.desc.format
+        );
+    }
+    let (source_size, [source_x, source_y, source_width, source_height]) =
+        blit_region(&source_info, source);
+    let (_, viewport) = blit_region(&destination_info, destination);
+
+    let mut resources = BLIT_RESOURCES.lock().unwrap();
+    let resources = resources
+        .entry(source_info.device)
+        .or_insert_with(|| BlitResources::new(source_info.device));
+    let pipeline = resources.pipeline(source_info.device, destination_info.desc.format);
+
+    let source_rect = create_rect_buffer(
+        source_info.device,
+        [
+            source_x / source_size.width as f32,
+            source_y / source_size.height as f32,
+            source_width / source_size.width as f32,
+            source_height / source_size.height as f32,
+        ],
+    );
+    let blit = Blit {
+        source: create_view(source.texture, source.mipLevel, source.origin.z),
+        source_rect,
+        sampler: resources.sampler(crate::device::map_filter_mode(filter)),
+        target: create_view(
+            destination.texture,
+            destination.mipLevel,
+            destination.origin.z,
+        ),
+        viewport,
+    };
+    blit.record(command_encoder, source_info.device, resources, pipeline);
+    gfx_select!(source_rect => GLOBAL.buffer_drop(source_rect, false));
+}
+
+/// Validates a blit region, returning the size of its mip level and the region as
+/// x, y, width and height.
+fn blit_region(
+    info: &crate::device::TextureInfo,
+    region: &native::WGPUBlitTexture,
+) -> (wgt::Extent3d, [f32; 4]) {
+    if info.desc.dimension != wgt::TextureDimension::D2 || info.desc.sample_count != 1 {
+        panic!("Blits are only supported between single-sampled 2D textures");
+    }
+    if region.mipLevel >= info.desc.mip_level_count {
+        panic!("Blit mip level is out of bounds of the texture");
+    }
+    if region.origin.z >= info.array_layer_count() || region.size.depth != 1 {
+        panic!("Blit regions must cover a single array layer of the texture");
+    }
+    let size = info.mip_level_size(region.mipLevel);
+    let in_bounds = |origin: u32, extent: u32, limit: u32| {
+        extent > 0 && origin.checked_add(extent).map_or(false, |end| end <= limit)
+    };
+    if !in_bounds(region.origin.x, region.size.width, size.width)
+        || !in_bounds(region.origin.y, region.size.height, size.height)
+    {
+        panic!("Blit region is empty or out of bounds of the texture");
+    }
+    (
+        size,
+        [
+            region.origin.x as f32,
+            region.origin.y as f32,
+            region.size.width as f32,
+            region.size.height as f32,
+        ],
+    )
+}
+
+/// Whether `format` can be sampled with filtering.
+fn is_blit_source(format: wgt::TextureFormat) -> bool {
+    format.describe().sample_type == wgt::TextureSampleType::Float { filterable: true }
+}
+
+/// Whether `format` can be rendered to from a float fragment output.
+fn is_blit_target(format: wgt::TextureFormat) -> bool {
+    let format_info = format.describe();
+    match format_info.sample_type {
+        wgt::TextureSampleType::Float { .. } => format_info
+            .guaranteed_format_features
+            .allowed_usages
+            .contains(wgt::TextureUsage::RENDER_ATTACHMENT),
+        _ => false,
+    }
+}
+
+/// Size of the source rectangle uniform: an offset and a scale, both `vec2<f32>`.
+const RECT_SIZE: u64 = 16;
+
+fn create_rect_buffer(device: id::DeviceId, rect: [f32; 4]) -> id::BufferId {
+    let buffer = check_error(gfx_select!(device => GLOBAL.device_create_buffer(
+        device,
+        &wgt::BufferDescriptor {
+            label: Some(Cow::Borrowed("blit rect")),
+            size: RECT_SIZE,
+            usage: wgt::BufferUsage::UNIFORM,
+            mapped_at_creation: true,
+        },
+        PhantomData
+    )));
+    let data = gfx_select!(buffer => GLOBAL.buffer_get_mapped_range(buffer, 0, NonZeroU64::new(RECT_SIZE)))
+        .expect("Unable to get mapped range")
+        .0;
+    unsafe {
+        ptr::copy_nonoverlapping(rect.as_ptr() as *const u8, data, RECT_SIZE as usize);
+    }
+    gfx_select!(buffer => GLOBAL.buffer_unmap(buffer)).expect("Unable to unmap buffer");
+    buffer
+}
+
+fn create_view(texture: id::TextureId, mip_level: u32, array_layer: u32) -> id::TextureViewId {
+    check_error(gfx_select!(texture => GLOBAL.texture_create_view(
+        texture,
+        &wgc::resource::TextureViewDescriptor {
+            label: Some(Cow::Borrowed("blit view")),
+            format: None,
+            dimension: Some(wgt::TextureViewDimension::D2),
+            aspect: wgt::TextureAspect::All,
+            base_mip_level: mip_level,
+            mip_level_count: NonZeroU32::new(1),
+            base_array_layer: array_layer,
+            array_layer_count: NonZeroU32::new(1),
+        },
+        PhantomData
+    )))
+}
+
+/// A single draw of a source texture view into a region of a target view. Both views
+/// are owned by the blit and released once it is recorded.
+struct Blit {
+    source: id::TextureViewId,
+    source_rect: id::BufferId,
+    sampler: id::SamplerId,
+    target: id::TextureViewId,
+    /// Region of the target to draw to, as x, y, width and height.
+    viewport: [f32; 4],
+}
+
+impl Blit {
+    unsafe fn record(
+        self,
+        command_encoder: id::CommandEncoderId,
+        device: id::DeviceId,
+        resources: &BlitResources,
+        pipeline: id::RenderPipelineId,
+    ) {
+        let entries = [
+            wgc::binding_model::BindGroupEntry {
+                binding: 0,
+                resource: wgc::binding_model::BindingResource::TextureView(self.source),
+            },
+            wgc::binding_model::BindGroupEntry {
+                binding: 1,
+                resource: wgc::binding_model::BindingResource::Sampler(self.sampler),
+            },
+            wgc::binding_model::BindGroupEntry {
+                binding: 2,
+                resource: wgc::binding_model::BindingResource::Buffer(
+                    wgc::binding_model::BufferBinding {
+                        buffer_id: self.source_rect,
+                        offset: 0,
+                        size: NonZeroU64::new(RECT_SIZE),
+                    },
+                ),
+            },
+        ];
+        let bind_group = check_error(gfx_select!(device => GLOBAL.device_create_bind_group(
+            device,
+            &wgc::binding_model::BindGroupDescriptor {
+                label: Some(Cow::Borrowed("blit bind group")),
+                layout: resources.bind_group_layout,
+                entries: Cow::Borrowed(&entries),
+            },
+            PhantomData
+        )));
+
+        let color_attachments = [wgc::command::RenderPassColorAttachment {
+            view: self.target,
+            resolve_target: None,
+            channel: wgc::command::PassChannel {
+                load_op: wgc::command::LoadOp::Load,
+                store_op: wgc::command::StoreOp::Store,
+                clear_value: wgt::Color::TRANSPARENT,
+                read_only: false,
+            },
+        }];
+        let desc = wgc::command::RenderPassDescriptor {
+            label: Some(Cow::Borrowed("blit")),
+            color_attachments: Cow::Borrowed(&color_attachments),
+            depth_stencil_attachment: None,
+        };
+        let mut pass = wgc::command::RenderPass::new(command_encoder, &desc);
+        let [x, y, width, height] = self.viewport;
+        render_ffi::wgpu_render_pass_set_viewport(&mut pass, x, y, width, height, 0.0, 1.0);
+        render_ffi::wgpu_render_pass_set_pipeline(&mut pass, pipeline);
+        render_ffi::wgpu_render_pass_set_bind_group(&mut pass, 0, bind_group, ptr::null(), 0);
+        render_ffi::wgpu_render_pass_draw(&mut pass, 3, 1, 0, 0);
+        gfx_select!(command_encoder => GLOBAL.command_encoder_run_render_pass(command_encoder, &pass))
+            .expect("Unable to record blit");
+
+        gfx_select!(bind_group => GLOBAL.bind_group_drop(bind_group));
+        for &view in &[self.source, self.target] {
+            gfx_select!(view => GLOBAL.texture_view_drop(view, false))
+                .expect("Unable to drop texture view");
+        }
+    }
+}
@@ -0,0 +1,42 @@
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] tex_coords: vec2<f32>;
+};
+
+// Covers the whole target with a single triangle.
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let x = i32(vertex_index) / 2;
+    let y = i32(vertex_index) & 1;
+    let tc = vec2<f32>(
+        f32(x) * 2.0,
+        f32(y) * 2.0
+    );
+    out.position = vec4<f32>(
+        tc.x * 2.0 - 1.0,
+        1.0 - tc.y * 2.0,
+        0.0, 1.0
+    );
+    out.tex_coords = tc;
+    return out;
+}
+
+// Region of the source texture to sample, in normalized coordinates.
+[[block]]
+struct Rect {
+    offset: vec2<f32>;
+    scale: vec2<f32>;
+};
+
+[[group(0), binding(0)]]
+var r_color: texture_2d<f32>;
+[[group(0), binding(1)]]
+var r_sampler: sampler;
+[[group(0), binding(2)]]
+var<uniform> r_rect: Rect;
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return textureSample(r_color, r_sampler, r_rect.offset + in.tex_coords * r_rect.scale);
+}
@@ -1,10 +1,15 @@
 use crate::{check_error, make_slice, map_enum, native, OwnedLabel, GLOBAL};
 use std::{
     borrow::Cow,
+    collections::HashMap,
+    marker::PhantomData,
     num::{NonZeroU32, NonZeroU64},
+    ops::Range,
+    ptr,
+    sync::Mutex,
 };
 use wgc::{
-    command::{compute_ffi, render_ffi},
+    command::{bundle_ffi, compute_ffi, render_ffi},
     gfx_select, id,
 };
 
@@ -46,10 +51,17 @@ pub extern "C" fn wgpuCommandEncoderCopyTextureToTexture(
     destination: &native::WGPUImageCopyTexture,
     copy_size: &native::WGPUExtent3D,
 ) {
+    let (source, destination) = match (
+        map_image_copy_texture(source),
+        map_image_copy_texture(destination),
+    ) {
+        (Some(source), Some(destination)) => (source, destination),
+        _ => return,
+    };
     gfx_select!(command_encoder => GLOBAL.command_encoder_copy_texture_to_texture(
         command_encoder,
-        &map_image_copy_texture(source),
-        &map_image_copy_texture(destination),
+        &source,
+        &destination,
         &map_extent3d(copy_size)))
     .expect("Unable to copy texture to texture")
 }
@@ -61,9 +73,13 @@ pub extern "C" fn wgpuCommandEncoderCopyTextureToBuffer(
     destination: &native::WGPUImageCopyBuffer,
     copy_size: &native::WGPUExtent3D,
 ) {
+    let source = match map_image_copy_texture(source) {
+        Some(source) => source,
+        None => return,
+    };
     gfx_select!(command_encoder => GLOBAL.command_encoder_copy_texture_to_buffer(
         command_encoder,
-        &map_image_copy_texture(source),
+        &source,
         &map_image_copy_buffer(destination),
         &map_extent3d(copy_size)))
     .expect("Unable to copy texture to buffer")
@@ -76,14 +92,362 @@ pub extern "C" fn wgpuCommandEncoderCopyBufferToTexture(
     destination: &native::WGPUImageCopyTexture,
     copy_size: &native::WGPUExtent3D,
 ) {
+    let destination = match map_image_copy_texture(destination) {
+        Some(destination) => destination,
+        None => return,
+    };
     gfx_select!(command_encoder => GLOBAL.command_encoder_copy_buffer_to_texture(
         command_encoder,
         &map_image_copy_buffer(source),
-        &map_image_copy_texture(destination),
+        &destination,
         &map_extent3d(copy_size)))
     .expect("Unable to copy buffer to texture")
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wgpuCommandEncoderClearBuffer(
+    command_encoder: id::CommandEncoderId,
+    buffer: id::BufferId,
+    offset: u64,
+    size: u64,
+) {
+    let info = match crate::device::buffer_info(buffer) {
+        Some(info) => info,
+        None => return,
+    };
+    if offset.checked_add(size).map_or(true, |end| end > info.size) {
+        panic!("Clear range is out of bounds of the buffer");
+    }
+    if size == 0 {
+        return;
+    }
+
+    let zeros = zero_buffer(info.device);
+    let mut cleared = 0;
+    while cleared < size {
+        let chunk_size = (size - cleared).min(ZERO_BUFFER_SIZE);
+        gfx_select!(command_encoder => GLOBAL.command_encoder_copy_buffer_to_buffer(
+            command_encoder,
+            zeros,
+            0,
+            buffer,
+            offset + cleared,
+            chunk_size))
+        .expect("Unable to clear buffer");
+        cleared += chunk_size;
+    }
+}
+
+/// Clears a range of subresources of `texture` to zero.
+///
+/// Color textures with `COPY_DST` usage are cleared by copying from a zeroed buffer,
+/// everything else needs `RENDER_ATTACHMENT` usage and is cleared with render passes.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuCommandEncoderClearTexture(
+    command_encoder: id::CommandEncoderId,
+    texture: id::TextureId,
+    range: &native::WGPUImageSubresourceRange,
+) {
+    let info = match crate::device::texture_info(texture) {
+        Some(info) => info,
+        None => return,
+    };
+    let aspect = crate::device::map_texture_aspect(range.aspect);
+    let mip_levels = subresource_range(
+        range.baseMipLevel,
+        range.mipLevelCount,
+        info.desc.mip_level_count,
+    )
+    .expect("Mip level range is out of bounds of the texture");
+    let array_layers = subresource_range(
+        range.baseArrayLayer,
+        range.arrayLayerCount,
+        info.array_layer_count(),
+    )
+    .expect("Array layer range is out of bounds of the texture");
+
+    let format_info = info.desc.format.describe();
+    let is_depth_stencil = format_info.sample_type == wgt::TextureSampleType::Depth;
+    if info.desc.usage.contains(wgt::TextureUsage::COPY_DST) && !is_depth_stencil {
+        clear_texture_by_copy(command_encoder, texture, &info, mip_levels, array_layers);
+    } else if info
+        .desc
+        .usage
+        .contains(wgt::TextureUsage::RENDER_ATTACHMENT)
+    {
+        for mip_level in mip_levels {
+            for array_layer in array_layers.clone() {
+                clear_texture_by_render_pass(
+                    command_encoder,
+                    texture,
+                    mip_level,
+                    array_layer,
+                    is_depth_stencil,
+                    aspect,
+                );
+            }
+        }
+    } else {
+        panic!("Clearing a texture requires COPY_DST or RENDER_ATTACHMENT usage");
+    }
+}
+
+/// Resolves a native `base`/`count` pair, where a count of 0 means "all remaining".
+fn subresource_range(base: u32, count: u32, total: u32) -> Option<Range<u32>> {
+    let count = if count == 0 {
+        total.checked_sub(base)?
+    } else {
+        count
+    };
+    let end = base.checked_add(count)?;
+    if end > total {
+        return None;
+    }
+    Some(base..end)
+}
+
+unsafe fn clear_texture_by_copy(
+    command_encoder: id::CommandEncoderId,
+    texture: id::TextureId,
+    info: &crate::device::TextureInfo,
+    mip_levels: Range<u32>,
+    array_layers: Range<u32>,
+) {
+    let format_info = info.desc.format.describe();
+    let (block_width, block_height) = (
+        format_info.block_dimensions.0 as u32,
+        format_info.block_dimensions.1 as u32,
+    );
+    let is_3d = info.desc.dimension == wgt::TextureDimension::D3;
+
+    let zeros = zero_buffer(info.device);
+    for mip_level in mip_levels {
+        let size = info.mip_level_size(mip_level);
+        let width_blocks = (size.width + block_width - 1) / block_width;
+        let height_blocks = (size.height + block_height - 1) / block_height;
+        let bytes_per_row = align_to(
+            (width_blocks * format_info.block_size as u32) as u64,
+            wgt::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+        );
+        // Each copy clears as many rows of blocks of one image as fit in the zero buffer.
+        let rows_per_copy = (ZERO_BUFFER_SIZE / bytes_per_row) as u32;
+        let images = if is_3d {
+            0..size.depth_or_array_layers
+        } else {
+            array_layers.clone()
+        };
+        for z in images {
+            let mut row = 0;
+            while row < height_blocks {
+                let rows = (height_blocks - row).min(rows_per_copy);
+                gfx_select!(command_encoder => GLOBAL.command_encoder_copy_buffer_to_texture(
+                command_encoder,
+                &wgt::ImageCopyBuffer {
+                    buffer: zeros,
+                    layout: wgt::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(bytes_per_row as u32),
+                        rows_per_image: None,
+                    },
+                },
+                &wgt::ImageCopyTexture {
+                    texture,
+                    mip_level,
+                    origin: wgt::Origin3d {
+                        x: 0,
+                        y: row * block_height,
+                        z,
+                    },
+                },
+                &wgt::Extent3d {
+                    width: width_blocks * block_width,
+                    height: rows * block_height,
+                    depth_or_array_layers: 1,
+                }))
+                .expect("Unable to clear texture");
+                row += rows;
+            }
+        }
+    }
+}
+
+unsafe fn clear_texture_by_render_pass(
+    command_encoder: id::CommandEncoderId,
+    texture: id::TextureId,
+    mip_level: u32,
+    array_layer: u32,
+    is_depth_stencil: bool,
+    aspect: wgt::TextureAspect,
+) {
+    let view = check_error(gfx_select!(texture => GLOBAL.texture_create_view(
+        texture,
+        &wgc::resource::TextureViewDescriptor {
+            label: Some(Cow::Borrowed("clear texture view")),
+            format: None,
+            dimension: Some(wgt::TextureViewDimension::D2),
+            aspect: wgt::TextureAspect::All,
+            base_mip_level: mip_level,
+            mip_level_count: NonZeroU32::new(1),
+            base_array_layer: array_layer,
+            array_layer_count: NonZeroU32::new(1),
+        },
+        PhantomData
+    )));
+
+    let color_attachments = if is_depth_stencil {
+        Vec::new()
+    } else {
+        vec![wgc::command::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            channel: clear_channel(true, wgt::Color::TRANSPARENT),
+        }]
+    };
+    let depth_stencil_attachment = if is_depth_stencil {
+        Some(wgc::command::RenderPassDepthStencilAttachment {
+            view,
+            depth: clear_channel(aspect != wgt::TextureAspect::StencilOnly, 0.0),
+            stencil: clear_channel(aspect != wgt::TextureAspect::DepthOnly, 0),
+        })
+    } else {
+        None
+    };
+    let desc = wgc::command::RenderPassDescriptor {
+        label: Some(Cow::Borrowed("clear texture")),
+        color_attachments: Cow::Owned(color_attachments),
+        depth_stencil_attachment: depth_stencil_attachment.as_ref(),
+    };
+    let pass = wgc::command::RenderPass::new(command_encoder, &desc);
+    gfx_select!(command_encoder => GLOBAL.command_encoder_run_render_pass(command_encoder, &pass))
+        .expect("Unable to clear texture");
+    gfx_select!(view => GLOBAL.texture_view_drop(view, false))
+        .expect("Unable to drop texture view");
+}
+
+fn clear_channel<V>(clear: bool, clear_value: V) -> wgc::command::PassChannel<V> {
+    wgc::command::PassChannel {
+        load_op: if clear {
+            wgc::command::LoadOp::Clear
+        } else {
+            wgc::command::LoadOp::Load
+        },
+        store_op: wgc::command::StoreOp::Store,
+        clear_value,
+        read_only: false,
+    }
+}
+
+/// Size of the zero-filled buffer clears copy from. Larger clears take several copies.
+const ZERO_BUFFER_SIZE: u64 = 1 << 20;
+
+lazy_static::lazy_static! {
+    static ref ZERO_BUFFERS: Mutex<HashMap<id::DeviceId, id::BufferId>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Drops the zero-filled buffer of a device that is being dropped.
+pub(crate) fn release_zero_buffer(device: id::DeviceId) {
+    if let Some(buffer) = ZERO_BUFFERS.lock().unwrap().remove(&device) {
+        gfx_select!(buffer => GLOBAL.buffer_drop(buffer, false));
+    }
+}
+
+/// Returns the device's zero-filled buffer, standing in for clear commands that
+/// wgpu-core doesn't have. It is created on first use and never written to again.
+unsafe fn zero_buffer(device: id::DeviceId) -> id::BufferId {
+    *ZERO_BUFFERS
+        .lock()
+        .unwrap()
+        .entry(device)
+        .or_insert_with(|| {
+            let buffer = check_error(gfx_select!(device => GLOBAL.device_create_buffer(
+                device,
+                &wgt::BufferDescriptor {
+                    label: Some(Cow::Borrowed("zero buffer")),
+                    size: ZERO_BUFFER_SIZE,
+                    usage: wgt::BufferUsage::COPY_SRC,
+                    mapped_at_creation: true,
+                },
+                PhantomData
+            )));
+            let data = gfx_select!(buffer => GLOBAL.buffer_get_mapped_range(buffer, 0, NonZeroU64::new(ZERO_BUFFER_SIZE)))
+                .expect("Unable to get mapped range")
+                .0;
+            ptr::write_bytes(data, 0, ZERO_BUFFER_SIZE as usize);
+            gfx_select!(buffer => GLOBAL.buffer_unmap(buffer)).expect("Unable to unmap buffer");
+            buffer
+        })
+}
+
+fn align_to(value: u64, alignment: u64) -> u64 {
+    match value % alignment {
+        0 => value,
+        rem => value + (alignment - rem),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuCommandEncoderPushDebugGroup(
+    command_encoder: id::CommandEncoderId,
+    group_label: *const std::os::raw::c_char,
+) {
+    let label = OwnedLabel::new(group_label)
+        .into_inner()
+        .unwrap_or_default();
+    gfx_select!(command_encoder => GLOBAL.command_encoder_push_debug_group(command_encoder, &label))
+        .expect("Unable to push debug group")
+}
+
+#[no_mangle]
+pub extern "C" fn wgpuCommandEncoderPopDebugGroup(command_encoder: id::CommandEncoderId) {
+    gfx_select!(command_encoder => GLOBAL.command_encoder_pop_debug_group(command_encoder))
+        .expect("Unable to pop debug group")
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuCommandEncoderInsertDebugMarker(
+    command_encoder: id::CommandEncoderId,
+    marker_label: *const std::os::raw::c_char,
+) {
+    let label = OwnedLabel::new(marker_label)
+        .into_inner()
+        .unwrap_or_default();
+    gfx_select!(command_encoder => GLOBAL.command_encoder_insert_debug_marker(command_encoder, &label))
+        .expect("Unable to insert debug marker")
+}
+
+#[no_mangle]
+pub extern "C" fn wgpuCommandEncoderWriteTimestamp(
+    command_encoder: id::CommandEncoderId,
+    query_set: id::QuerySetId,
+    query_index: u32,
+) {
+    gfx_select!(command_encoder => GLOBAL.command_encoder_write_timestamp(
+        command_encoder,
+        query_set,
+        query_index))
+    .expect("Unable to write timestamp")
+}
+
+#[no_mangle]
+pub extern "C" fn wgpuCommandEncoderResolveQuerySet(
+    command_encoder: id::CommandEncoderId,
+    query_set: id::QuerySetId,
+    first_query: u32,
+    query_count: u32,
+    destination: id::BufferId,
+    destination_offset: u64,
+) {
+    gfx_select!(command_encoder => GLOBAL.command_encoder_resolve_query_set(
+        command_encoder,
+        query_set,
+        first_query,
+        query_count,
+        destination,
+        destination_offset))
+    .expect("Unable to resolve query set")
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuCommandEncoderBeginComputePass(
     encoder: id::CommandEncoderId,
@@ -103,7 +467,9 @@ pub unsafe extern "C" fn wgpuCommandEncoderBeginRenderPass(
 ) -> id::RenderPassEncoderId {
     let depth_stencil_attachment = descriptor.depthStencilAttachment.as_ref().map(|desc| {
         wgc::command::RenderPassDepthStencilAttachment {
-            view: desc.attachment,
+            view: desc
+                .attachment
+                .expect("Depth stencil attachment view must be provided"),
             depth: wgc::command::PassChannel {
                 load_op: map_load_op(desc.depthLoadOp),
                 store_op: map_store_op(desc.depthStoreOp),
@@ -127,8 +493,10 @@ pub unsafe extern "C" fn wgpuCommandEncoderBeginRenderPass(
             )
             .iter()
             .map(|color_attachment| wgc::command::RenderPassColorAttachment {
-                view: color_attachment.attachment,
-                resolve_target: Some(color_attachment.resolveTarget),
+                view: color_attachment
+                    .attachment
+                    .expect("Color attachment view must be provided"),
+                resolve_target: color_attachment.resolveTarget,
                 channel: wgc::command::PassChannel {
                     load_op: map_load_op(color_attachment.loadOp),
                     store_op: map_store_op(color_attachment.storeOp),
@@ -226,6 +594,16 @@ pub unsafe extern "C" fn wgpuComputePassEncoderDispatch(
     compute_ffi::wgpu_compute_pass_dispatch(pass, x, y, z);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wgpuComputePassEncoderDispatchIndirect(
+    pass: id::ComputePassEncoderId,
+    indirect_buffer: id::BufferId,
+    indirect_offset: u64,
+) {
+    let pass = pass.as_mut().expect("Compute pass invalid");
+    compute_ffi::wgpu_compute_pass_dispatch_indirect(pass, indirect_buffer, indirect_offset);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuRenderPassEncoderDraw(
     pass: id::RenderPassEncoderId,
@@ -264,6 +642,88 @@ pub unsafe extern "C" fn wgpuRenderPassEncoderDrawIndexed(
     );
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderDrawIndirect(
+    pass: id::RenderPassEncoderId,
+    indirect_buffer: id::BufferId,
+    indirect_offset: u64,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_draw_indirect(pass, indirect_buffer, indirect_offset);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderDrawIndexedIndirect(
+    pass: id::RenderPassEncoderId,
+    indirect_buffer: id::BufferId,
+    indirect_offset: u64,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_draw_indexed_indirect(pass, indirect_buffer, indirect_offset);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderMultiDrawIndirect(
+    pass: id::RenderPassEncoderId,
+    buffer: id::BufferId,
+    offset: u64,
+    count: u32,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_multi_draw_indirect(pass, buffer, offset, count);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderMultiDrawIndexedIndirect(
+    pass: id::RenderPassEncoderId,
+    buffer: id::BufferId,
+    offset: u64,
+    count: u32,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_multi_draw_indexed_indirect(pass, buffer, offset, count);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderMultiDrawIndirectCount(
+    pass: id::RenderPassEncoderId,
+    buffer: id::BufferId,
+    offset: u64,
+    count_buffer: id::BufferId,
+    count_buffer_offset: u64,
+    max_count: u32,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_multi_draw_indirect_count(
+        pass,
+        buffer,
+        offset,
+        count_buffer,
+        count_buffer_offset,
+        max_count,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderMultiDrawIndexedIndirectCount(
+    pass: id::RenderPassEncoderId,
+    buffer: id::BufferId,
+    offset: u64,
+    count_buffer: id::BufferId,
+    count_buffer_offset: u64,
+    max_count: u32,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_multi_draw_indexed_indirect_count(
+        pass,
+        buffer,
+        offset,
+        count_buffer,
+        count_buffer_offset,
+        max_count,
+    );
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuRenderPassEncoderSetIndexBuffer(
     pass: id::RenderPassEncoderId,
@@ -299,6 +759,119 @@ pub unsafe extern "C" fn wgpuRenderPassEncoderSetVertexBuffer(
     );
 }
 
+/// wgpu-core reads pass labels with `CStr::from_ptr`, so NULL is replaced by an empty label.
+fn label_or_empty(label: *const std::os::raw::c_char) -> *const std::os::raw::c_char {
+    if label.is_null() {
+        b"\0".as_ptr() as *const std::os::raw::c_char
+    } else {
+        label
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuComputePassEncoderPushDebugGroup(
+    pass: id::ComputePassEncoderId,
+    group_label: *const std::os::raw::c_char,
+) {
+    let pass = pass.as_mut().expect("Compute pass invalid");
+    compute_ffi::wgpu_compute_pass_push_debug_group(pass, label_or_empty(group_label), 0);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuComputePassEncoderPopDebugGroup(pass: id::ComputePassEncoderId) {
+    let pass = pass.as_mut().expect("Compute pass invalid");
+    compute_ffi::wgpu_compute_pass_pop_debug_group(pass);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuComputePassEncoderInsertDebugMarker(
+    pass: id::ComputePassEncoderId,
+    marker_label: *const std::os::raw::c_char,
+) {
+    let pass = pass.as_mut().expect("Compute pass invalid");
+    compute_ffi::wgpu_compute_pass_insert_debug_marker(pass, label_or_empty(marker_label), 0);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderPushDebugGroup(
+    pass: id::RenderPassEncoderId,
+    group_label: *const std::os::raw::c_char,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_push_debug_group(pass, label_or_empty(group_label), 0);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderPopDebugGroup(pass: id::RenderPassEncoderId) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_pop_debug_group(pass);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderInsertDebugMarker(
+    pass: id::RenderPassEncoderId,
+    marker_label: *const std::os::raw::c_char,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_insert_debug_marker(pass, label_or_empty(marker_label), 0);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuComputePassEncoderWriteTimestamp(
+    pass: id::ComputePassEncoderId,
+    query_set: id::QuerySetId,
+    query_index: u32,
+) {
+    let pass = pass.as_mut().expect("Compute pass invalid");
+    compute_ffi::wgpu_compute_pass_write_timestamp(pass, query_set, query_index);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderWriteTimestamp(
+    pass: id::RenderPassEncoderId,
+    query_set: id::QuerySetId,
+    query_index: u32,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_write_timestamp(pass, query_set, query_index);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuComputePassEncoderBeginPipelineStatisticsQuery(
+    pass: id::ComputePassEncoderId,
+    query_set: id::QuerySetId,
+    query_index: u32,
+) {
+    let pass = pass.as_mut().expect("Compute pass invalid");
+    compute_ffi::wgpu_compute_pass_begin_pipeline_statistics_query(pass, query_set, query_index);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuComputePassEncoderEndPipelineStatisticsQuery(
+    pass: id::ComputePassEncoderId,
+) {
+    let pass = pass.as_mut().expect("Compute pass invalid");
+    compute_ffi::wgpu_compute_pass_end_pipeline_statistics_query(pass);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderBeginPipelineStatisticsQuery(
+    pass: id::RenderPassEncoderId,
+    query_set: id::QuerySetId,
+    query_index: u32,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_begin_pipeline_statistics_query(pass, query_set, query_index);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderEndPipelineStatisticsQuery(
+    pass: id::RenderPassEncoderId,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_end_pipeline_statistics_query(pass);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuRenderPassEncoderSetPushConstants(
     pass: id::RenderPassEncoderId,
@@ -361,6 +934,151 @@ pub unsafe extern "C" fn wgpuRenderPassEncoderSetScissorRect(
     render_ffi::wgpu_render_pass_set_scissor_rect(pass, x, y, w, h);
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderPassEncoderExecuteBundles(
+    pass: id::RenderPassEncoderId,
+    bundles_count: u32,
+    bundles: *const id::RenderBundleId,
+) {
+    let pass = pass.as_mut().expect("Render pass invalid");
+    render_ffi::wgpu_render_pass_execute_bundles(pass, bundles, bundles_count as usize);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderFinish(
+    bundle: id::RenderBundleEncoderId,
+    descriptor: &native::WGPURenderBundleDescriptor,
+) -> id::RenderBundleId {
+    let bundle = Box::from_raw(bundle);
+    let device_id = bundle.parent();
+    let desc = wgt::RenderBundleDescriptor {
+        label: OwnedLabel::new(descriptor.label).into_cow(),
+    };
+    check_error(
+        gfx_select!(device_id => GLOBAL.render_bundle_encoder_finish(*bundle, &desc, PhantomData)),
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderSetPipeline(
+    bundle: id::RenderBundleEncoderId,
+    pipeline_id: id::RenderPipelineId,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle_ffi::wgpu_render_bundle_set_pipeline(bundle, pipeline_id);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderSetBindGroup(
+    bundle: id::RenderBundleEncoderId,
+    group_index: u32,
+    group: id::BindGroupId,
+    dynamic_offset_count: u32,
+    dynamic_offsets: *const u32,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle_ffi::wgpu_render_bundle_set_bind_group(
+        bundle,
+        group_index,
+        group,
+        dynamic_offsets,
+        dynamic_offset_count as usize,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderSetIndexBuffer(
+    bundle: id::RenderBundleEncoderId,
+    buffer: id::BufferId,
+    index_format: native::WGPUIndexFormat,
+    offset: u64,
+    size: u64,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle.set_index_buffer(
+        buffer,
+        crate::device::map_index_format(index_format).expect("Index format cannot be undefined"),
+        offset,
+        NonZeroU64::new(size),
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderSetVertexBuffer(
+    bundle: id::RenderBundleEncoderId,
+    slot: u32,
+    buffer: id::BufferId,
+    offset: u64,
+    size: u64,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle_ffi::wgpu_render_bundle_set_vertex_buffer(
+        bundle,
+        slot,
+        buffer,
+        offset,
+        NonZeroU64::new(size),
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderDraw(
+    bundle: id::RenderBundleEncoderId,
+    vertex_count: u32,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle_ffi::wgpu_render_bundle_draw(
+        bundle,
+        vertex_count,
+        instance_count,
+        first_vertex,
+        first_instance,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderDrawIndexed(
+    bundle: id::RenderBundleEncoderId,
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle_ffi::wgpu_render_bundle_draw_indexed(
+        bundle,
+        index_count,
+        instance_count,
+        first_index,
+        base_vertex,
+        first_instance,
+    );
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderDrawIndirect(
+    bundle: id::RenderBundleEncoderId,
+    indirect_buffer: id::BufferId,
+    indirect_offset: u64,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle_ffi::wgpu_render_bundle_draw_indirect(bundle, indirect_buffer, indirect_offset);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuRenderBundleEncoderDrawIndexedIndirect(
+    bundle: id::RenderBundleEncoderId,
+    indirect_buffer: id::BufferId,
+    indirect_offset: u64,
+) {
+    let bundle = bundle.as_mut().expect("Render bundle invalid");
+    bundle_ffi::wgpu_render_pass_bundle_indexed_indirect(bundle, indirect_buffer, indirect_offset);
+}
+
 pub fn map_extent3d(native: &native::WGPUExtent3D) -> wgt::Extent3d {
     wgt::Extent3d {
         width: native.width,
@@ -377,14 +1095,40 @@ pub fn map_origin3d(native: &native::WGPUOrigin3D) -> wgt::Origin3d {
     }
 }
 
+/// Returns `None` if the copy is invalid, which has been logged.
 pub fn map_image_copy_texture(
     native: &native::WGPUImageCopyTexture,
-) -> wgc::command::ImageCopyTexture {
-    wgt::ImageCopyTexture {
+) -> Option<wgc::command::ImageCopyTexture> {
+    // wgpu-core always copies every aspect of the texture. Selecting the only aspect of a
+    // format is the same copy, but a single plane of a depth-stencil texture can't be
+    // expressed yet. Refuse rather than copy the wrong data.
+    let aspect = crate::device::map_texture_aspect(native.aspect);
+    if aspect != wgt::TextureAspect::All {
+        let format = crate::device::texture_info(native.texture)?.desc.format;
+        let (has_depth, has_stencil) = match format {
+            wgt::TextureFormat::Depth32Float | wgt::TextureFormat::Depth24Plus => (true, false),
+            wgt::TextureFormat::Depth24PlusStencil8 => (true, true),
+            _ => (false, false),
+        };
+        let covers_format = match aspect {
+            wgt::TextureAspect::DepthOnly => has_depth && !has_stencil,
+            wgt::TextureAspect::StencilOnly => has_stencil && !has_depth,
+            wgt::TextureAspect::All => true,
+        };
+        if !covers_format {
+            log::error!(
+                "Copying the {:?} aspect of a {:?} texture is not supported",
+                aspect,
+                format
+            );
+            return None;
+        }
+    }
+    Some(wgt::ImageCopyTexture {
         texture: native.texture,
         mip_level: native.mipLevel,
         origin: map_origin3d(&native.origin),
-    }
+    })
 }
 
 pub fn map_image_copy_buffer(
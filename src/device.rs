@@ -1,11 +1,18 @@
 use crate::{check_error, follow_chain, make_slice, map_enum, native, Label, OwnedLabel, GLOBAL};
 use std::{
     borrow::Cow,
+    collections::{hash_map::Entry, HashMap},
     convert::TryInto,
     ffi::CStr,
     marker::PhantomData,
+    mem,
     num::{NonZeroU32, NonZeroU64, NonZeroU8},
+    ops::Range,
     path::Path,
+    ptr,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
 };
 use wgc::{gfx_select, id, pipeline::ShaderModuleSource};
 
@@ -56,7 +63,7 @@ fn map_device_descriptor<'a>(
         (
             wgt::DeviceDescriptor {
                 label: OwnedLabel::new(extras.label).into_cow(),
-                features: wgt::Features::empty(),
+                features: map_native_features(extras.nativeFeatures),
                 limits: wgt::Limits {
                     max_bind_groups: extras.maxBindGroups,
                     ..wgt::Limits::default()
@@ -76,6 +83,45 @@ fn map_device_descriptor<'a>(
     }
 }
 
+fn map_native_features(native_features: native::WGPUNativeFeatureFlags) -> wgt::Features {
+    let mut features = wgt::Features::empty();
+    for &(native_feature, feature) in &[
+        (
+            native::WGPUNativeFeature_MultiDrawIndirect,
+            wgt::Features::MULTI_DRAW_INDIRECT,
+        ),
+        (
+            native::WGPUNativeFeature_MultiDrawIndirectCount,
+            wgt::Features::MULTI_DRAW_INDIRECT_COUNT,
+        ),
+        (
+            native::WGPUNativeFeature_TimestampQuery,
+            wgt::Features::TIMESTAMP_QUERY,
+        ),
+        (
+            native::WGPUNativeFeature_PipelineStatisticsQuery,
+            wgt::Features::PIPELINE_STATISTICS_QUERY,
+        ),
+        (
+            native::WGPUNativeFeature_TextureCompressionBc,
+            wgt::Features::TEXTURE_COMPRESSION_BC,
+        ),
+        (
+            native::WGPUNativeFeature_TextureCompressionEtc2,
+            wgt::Features::TEXTURE_COMPRESSION_ETC2,
+        ),
+        (
+            native::WGPUNativeFeature_TextureCompressionAstcLdr,
+            wgt::Features::TEXTURE_COMPRESSION_ASTC_LDR,
+        ),
+    ] {
+        if native_features & native_feature as native::WGPUNativeFeatureFlags != 0 {
+            features |= feature;
+        }
+    }
+    features
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuDeviceCreateShaderModule(
     device: id::DeviceId,
@@ -88,13 +134,27 @@ pub unsafe extern "C" fn wgpuDeviceCreateShaderModule(
         WGPUSType_ShaderModuleWGSLDescriptor => native::WGPUShaderModuleWGSLDescriptor)
     );
 
+    let source_copy = crate::shader::ShaderSourceCopy::new(&source);
+
     let desc = wgc::pipeline::ShaderModuleDescriptor {
         label: label.as_cow(),
         flags: wgt::ShaderFlags::VALIDATION,
     };
-    check_error(
+    let id = check_error(
         gfx_select!(device => GLOBAL.device_create_shader_module(device, &desc, source, PhantomData)),
-    )
+    );
+    if let Some(source_copy) = source_copy {
+        source_copy.register(id);
+    }
+    id
+}
+
+/// Drops a shader module along with the source copy kept for specializing it.
+/// Pipelines created from the module remain valid.
+#[no_mangle]
+pub extern "C" fn wgpuShaderModuleDrop(module: id::ShaderModuleId) {
+    crate::shader::release_shader_source(module);
+    gfx_select!(module => GLOBAL.shader_module_drop(module));
 }
 
 fn map_shader_module<'a>(
@@ -121,7 +181,7 @@ pub extern "C" fn wgpuDeviceCreateBuffer(
 ) -> id::BufferId {
     let usage = wgt::BufferUsage::from_bits(desc.usage).expect("Buffer Usage Invalid.");
     let label = OwnedLabel::new(desc.label);
-    check_error(gfx_select!(device => GLOBAL.device_create_buffer(
+    let id = check_error(gfx_select!(device => GLOBAL.device_create_buffer(
         device,
         &wgt::BufferDescriptor {
             label: label.as_cow(),
@@ -130,14 +190,137 @@ pub extern "C" fn wgpuDeviceCreateBuffer(
             mapped_at_creation: desc.mappedAtCreation,
         },
         PhantomData
-    )))
+    )));
+    BUFFERS.lock().unwrap().insert(
+        id,
+        BufferInfo {
+            device,
+            size: desc.size,
+        },
+    );
+    if desc.mappedAtCreation {
+        MAPPED_BUFFERS
+            .lock()
+            .unwrap()
+            .insert(id, MappedBuffer::new(0..desc.size, true));
+    }
+    id
+}
+
+/// Creates a buffer holding `data`, padded with zeroes to a multiple of
+/// `COPY_BUFFER_ALIGNMENT`. The descriptor's size and `mappedAtCreation` are ignored.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuDeviceCreateBufferInit(
+    device: id::DeviceId,
+    descriptor: &native::WGPUBufferDescriptor,
+    data: *const u8,
+    data_size: usize,
+) -> id::BufferId {
+    let unpadded_size = data_size as wgt::BufferAddress;
+    let padding = (wgt::COPY_BUFFER_ALIGNMENT - unpadded_size % wgt::COPY_BUFFER_ALIGNMENT)
+        % wgt::COPY_BUFFER_ALIGNMENT;
+    let padded_size = unpadded_size + padding;
+
+    let buffer = wgpuDeviceCreateBuffer(
+        device,
+        &native::WGPUBufferDescriptor {
+            size: padded_size,
+            mappedAtCreation: true,
+            ..*descriptor
+        },
+    );
+    if padded_size != 0 {
+        let mapped = wgpuBufferGetMappedRange(buffer, 0, padded_size as usize);
+        ptr::copy_nonoverlapping(data, mapped, data_size);
+        ptr::write_bytes(mapped.add(data_size), 0, padding as usize);
+    }
+    wgpuBufferUnmap(buffer);
+    buffer
+}
+
+/// Creation parameters of a buffer, which wgpu-core doesn't hand back out.
+#[derive(Clone, Debug)]
+pub(crate) struct BufferInfo {
+    pub(crate) device: id::DeviceId,
+    pub(crate) size: wgt::BufferAddress,
+}
+
+/// Creation parameters of a texture, which wgpu-core doesn't hand back out.
+#[derive(Clone, Debug)]
+pub(crate) struct TextureInfo {
+    pub(crate) device: id::DeviceId,
+    pub(crate) desc: wgt::TextureDescriptor<()>,
+}
+
+impl TextureInfo {
+    /// Size of the given mip level, in texels.
+    pub(crate) fn mip_level_size(&self, mip_level: u32) -> wgt::Extent3d {
+        let size = self.desc.size;
+        wgt::Extent3d {
+            width: (size.width >> mip_level).max(1),
+            height: (size.height >> mip_level).max(1),
+            depth_or_array_layers: match self.desc.dimension {
+                wgt::TextureDimension::D3 => (size.depth_or_array_layers >> mip_level).max(1),
+                _ => size.depth_or_array_layers,
+            },
+        }
+    }
+
+    pub(crate) fn array_layer_count(&self) -> u32 {
+        match self.desc.dimension {
+            wgt::TextureDimension::D3 => 1,
+            _ => self.desc.size.depth_or_array_layers,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref BUFFERS: Mutex<HashMap<id::BufferId, BufferInfo>> = Mutex::new(HashMap::new());
+    static ref TEXTURES: Mutex<HashMap<id::TextureId, TextureInfo>> = Mutex::new(HashMap::new());
+}
+
+/// Looks up the creation parameters of `buffer`, logging an error if it is unknown, such
+/// as when it was dropped already.
+pub(crate) fn buffer_info(buffer: id::BufferId) -> Option<BufferInfo> {
+    let info = BUFFERS.lock().unwrap().get(&buffer).cloned();
+    if info.is_none() {
+        log::error!("Buffer {:?} is destroyed or unknown", buffer);
+    }
+    info
+}
+
+/// Looks up the creation parameters of `texture`, logging an error if it is unknown, such
+/// as when it was dropped already or belongs to a swap chain.
+pub(crate) fn texture_info(texture: id::TextureId) -> Option<TextureInfo> {
+    let info = TEXTURES.lock().unwrap().get(&texture).cloned();
+    if info.is_none() {
+        log::error!("Texture {:?} is destroyed or unknown", texture);
+    }
+    info
+}
+
+/// Forgets everything kept about a buffer that is being destroyed or dropped.
+unsafe fn release_buffer(buffer: id::BufferId) {
+    abort_pending_map(
+        buffer,
+        native::WGPUBufferMapAsyncStatus_DestroyedBeforeCallback,
+    );
+    MAPPED_BUFFERS.lock().unwrap().remove(&buffer);
+    BUFFERS.lock().unwrap().remove(&buffer);
 }
 
 #[no_mangle]
-pub extern "C" fn wgpu_buffer_destroy(buffer_id: id::BufferId) {
+pub unsafe extern "C" fn wgpu_buffer_destroy(buffer_id: id::BufferId) {
+    release_buffer(buffer_id);
     gfx_select!(buffer_id => GLOBAL.buffer_destroy(buffer_id)).expect("Unable to destroy buffer");
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wgpuBufferDrop(buffer: id::BufferId) {
+    release_buffer(buffer);
+    gfx_select!(buffer => GLOBAL.buffer_drop(buffer, false));
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuDeviceCreateBindGroupLayout(
     device: id::DeviceId,
@@ -249,12 +432,16 @@ pub unsafe extern "C" fn wgpuDeviceCreateComputePipeline(
     device: id::DeviceId,
     descriptor: &native::WGPUComputePipelineDescriptor,
 ) -> id::ComputePipelineId {
-    let stage = wgc::pipeline::ProgrammableStageDescriptor {
-        module: descriptor.computeStage.module,
-        entry_point: OwnedLabel::new(descriptor.computeStage.entryPoint)
-            .into_cow()
-            .expect("Entry point not provided"),
-    };
+    let stage_extras = follow_chain!(
+        get_stage_extras(&descriptor.computeStage,
+        WGPUSType_ProgrammableStageExtras => native::WGPUProgrammableStageExtras)
+    );
+    let stage = map_programmable_stage(
+        device,
+        descriptor.computeStage.module,
+        descriptor.computeStage.entryPoint,
+        stage_extras,
+    );
     let desc = wgc::pipeline::ComputePipelineDescriptor {
         label: OwnedLabel::new(descriptor.label).into_cow(),
         layout: Some(descriptor.layout),
@@ -266,6 +453,41 @@ pub unsafe extern "C" fn wgpuDeviceCreateComputePipeline(
     check_error((id, error))
 }
 
+fn get_stage_extras<'a, T>(
+    _: &T,
+    extras: Option<&'a native::WGPUProgrammableStageExtras>,
+) -> Option<&'a native::WGPUProgrammableStageExtras> {
+    extras
+}
+
+unsafe fn map_programmable_stage<'a>(
+    device: id::DeviceId,
+    module: id::ShaderModuleId,
+    entry_point: *const std::os::raw::c_char,
+    extras: Option<&native::WGPUProgrammableStageExtras>,
+) -> wgc::pipeline::ProgrammableStageDescriptor<'a> {
+    let module = match extras {
+        Some(extras) if extras.constantCount > 0 => {
+            let specialized = crate::shader::specialize_shader_module(
+                device,
+                module,
+                make_slice(extras.constants, extras.constantCount as usize),
+            );
+            match specialized {
+                Ok(specialized) => specialized,
+                Err(error) => check_error((module, Some(error))),
+            }
+        }
+        _ => module,
+    };
+    wgc::pipeline::ProgrammableStageDescriptor {
+        module,
+        entry_point: OwnedLabel::new(entry_point)
+            .into_cow()
+            .expect("Entry point not provided"),
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuDeviceCreateCommandEncoder(
     device: id::DeviceId,
@@ -279,6 +501,56 @@ pub unsafe extern "C" fn wgpuDeviceCreateCommandEncoder(
     )
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wgpuDeviceCreateRenderBundleEncoder(
+    device: id::DeviceId,
+    descriptor: &native::WGPURenderBundleEncoderDescriptor,
+) -> id::RenderBundleEncoderId {
+    validate_sample_count(descriptor.sampleCount);
+    let desc = wgc::command::RenderBundleEncoderDescriptor {
+        label: OwnedLabel::new(descriptor.label).into_cow(),
+        color_formats: Cow::Owned(
+            make_slice(
+                descriptor.colorFormats,
+                descriptor.colorFormatsCount as usize,
+            )
+            .iter()
+            .map(|format| map_texture_format(*format).expect("Texture format must be defined"))
+            .collect(),
+        ),
+        depth_stencil_format: map_texture_format(descriptor.depthStencilFormat),
+        sample_count: descriptor.sampleCount,
+    };
+    check_error(GLOBAL.device_create_render_bundle_encoder(device, &desc))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuDeviceCreateQuerySet(
+    device: id::DeviceId,
+    descriptor: &native::WGPUQuerySetDescriptor,
+) -> id::QuerySetId {
+    let ty = match descriptor.type_ {
+        native::WGPUQueryType_Timestamp => wgt::QueryType::Timestamp,
+        native::WGPUQueryType_PipelineStatistics => wgt::QueryType::PipelineStatistics(
+            make_slice(
+                descriptor.pipelineStatistics,
+                descriptor.pipelineStatisticsCount as usize,
+            )
+            .iter()
+            .fold(wgt::PipelineStatisticsTypes::empty(), |types, name| {
+                types | map_pipeline_statistic_name(*name)
+            }),
+        ),
+        x => panic!("Unsupported query type: {}", x),
+    };
+    let desc = wgt::QuerySetDescriptor {
+        label: OwnedLabel::new(descriptor.label).into_cow(),
+        ty,
+        count: descriptor.count,
+    };
+    check_error(gfx_select!(device => GLOBAL.device_create_query_set(device, &desc, PhantomData)))
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuDeviceGetQueue(device: id::DeviceId) -> id::QueueId {
     device
@@ -291,8 +563,47 @@ pub unsafe extern "C" fn wgpuQueueSubmit(
     command_buffers: *const id::CommandBufferId,
 ) {
     let command_buffer_ids = make_slice(command_buffers, command_count as usize);
+    SUBMISSIONS
+        .lock()
+        .unwrap()
+        .entry(queue)
+        .or_default()
+        .untracked_submitted += 1;
     gfx_select!(queue => GLOBAL.queue_submit(queue, command_buffer_ids))
-        .expect("Unable to submit queue")
+        .expect("Unable to submit queue");
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuQueueSubmitForIndex(
+    queue: id::QueueId,
+    command_count: u32,
+    command_buffers: *const id::CommandBufferId,
+) -> native::WGPUSubmissionIndex {
+    let command_buffer_ids = make_slice(command_buffers, command_count as usize);
+    let (index, untracked) = {
+        let mut submissions = SUBMISSIONS.lock().unwrap();
+        let tracker = submissions.entry(queue).or_default();
+        tracker.submitted += 1;
+        (tracker.submitted, tracker.untracked_submitted)
+    };
+    submit_with_marker(
+        queue,
+        command_buffer_ids,
+        Box::new(move |_| {
+            let mut submissions = SUBMISSIONS.lock().unwrap();
+            let tracker = submissions.entry(queue).or_default();
+            tracker.completed = tracker.completed.max(index);
+            // Untracked submissions made before this one are done as well.
+            tracker.untracked_completed = tracker.untracked_completed.max(untracked);
+        }),
+    );
+    index
+}
+
+#[no_mangle]
+pub extern "C" fn wgpuQueueGetTimestampPeriod(queue: id::QueueId) -> f32 {
+    gfx_select!(queue => GLOBAL.queue_get_timestamp_period(queue))
+        .expect("Unable to get timestamp period")
 }
 
 #[no_mangle]
@@ -308,6 +619,157 @@ pub unsafe extern "C" fn wgpuQueueWriteBuffer(
         .expect("Unable to write buffer")
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn wgpuQueueWriteTexture(
+    queue: id::QueueId,
+    destination: &native::WGPUImageCopyTexture,
+    data: *const u8,
+    data_size: usize,
+    data_layout: &native::WGPUTextureDataLayout,
+    write_size: &native::WGPUExtent3D,
+) {
+    let destination = match crate::command::map_image_copy_texture(destination) {
+        Some(destination) => destination,
+        None => return,
+    };
+    let slice = make_slice(data, data_size);
+    gfx_select!(queue => GLOBAL.queue_write_texture(
+        queue,
+        &destination,
+        slice,
+        &crate::command::map_texture_data_layout(data_layout),
+        &crate::command::map_extent3d(write_size)))
+    .expect("Unable to write texture")
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuQueueOnSubmittedWorkDone(
+    queue: id::QueueId,
+    callback: native::WGPUQueueWorkDoneCallback,
+    userdata: *mut std::os::raw::c_void,
+) {
+    let callback = callback.expect("Callback cannot be null");
+    submit_with_marker(
+        queue,
+        &[],
+        Box::new(move |status| {
+            let status = match map_buffer_map_status(status) {
+                native::WGPUBufferMapAsyncStatus_Success => native::WGPUQueueWorkDoneStatus_Success,
+                native::WGPUBufferMapAsyncStatus_Error => native::WGPUQueueWorkDoneStatus_Error,
+                native::WGPUBufferMapAsyncStatus_DeviceLost => {
+                    native::WGPUQueueWorkDoneStatus_DeviceLost
+                }
+                _ => native::WGPUQueueWorkDoneStatus_Unknown,
+            };
+            callback(status, userdata);
+        }),
+    );
+}
+
+type WorkDoneClosure = Box<dyn FnOnce(wgc::resource::BufferMapAsyncStatus)>;
+
+struct WorkDoneMarker {
+    queue: id::QueueId,
+    buffer: id::BufferId,
+    closure: WorkDoneClosure,
+}
+
+/// Submissions made through `wgpuQueueSubmitForIndex`. Those made through
+/// `wgpuQueueSubmit` are only counted, to keep them free of marker overhead, and retire
+/// with the next marker submitted after them.
+#[derive(Default)]
+struct SubmissionTracker {
+    submitted: native::WGPUSubmissionIndex,
+    completed: native::WGPUSubmissionIndex,
+    /// Number of untracked submissions, and how many of them are known to be done.
+    untracked_submitted: u64,
+    untracked_completed: u64,
+    /// Whether a marker retiring the untracked submissions is in flight.
+    retiring_untracked: bool,
+}
+
+impl SubmissionTracker {
+    fn is_idle(&self) -> bool {
+        self.completed == self.submitted && self.untracked_completed == self.untracked_submitted
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SUBMISSIONS: Mutex<HashMap<id::QueueId, SubmissionTracker>> =
+        Mutex::new(HashMap::new());
+    /// Idle marker buffers of each queue, reused across submissions.
+    static ref MARKER_POOL: Mutex<HashMap<id::QueueId, Vec<id::BufferId>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Submits `command_buffers` and runs `closure` from `wgpuDevicePoll` once this
+/// submission and all work submitted before it is done.
+///
+/// wgpu-core has no fence API, so this writes into a tiny marker buffer, which gets
+/// flushed as part of the submission, and then maps the marker. Submissions retire in
+/// order, hence the mapping resolves only after everything submitted up to here.
+unsafe fn submit_with_marker(
+    queue: id::QueueId,
+    command_buffers: &[id::CommandBufferId],
+    closure: WorkDoneClosure,
+) {
+    let pooled = MARKER_POOL
+        .lock()
+        .unwrap()
+        .get_mut(&queue)
+        .and_then(|pool| pool.pop());
+    let buffer = match pooled {
+        Some(buffer) => buffer,
+        None => check_error(gfx_select!(queue => GLOBAL.device_create_buffer(
+            queue,
+            &wgt::BufferDescriptor {
+                label: Some(Cow::Borrowed("work done marker")),
+                size: wgt::COPY_BUFFER_ALIGNMENT,
+                usage: wgt::BufferUsage::MAP_READ | wgt::BufferUsage::COPY_DST,
+                mapped_at_creation: false,
+            },
+            PhantomData
+        ))),
+    };
+    gfx_select!(queue => GLOBAL.queue_write_buffer(queue, buffer, 0, &[0; 4]))
+        .expect("Unable to write work done marker");
+    gfx_select!(queue => GLOBAL.queue_submit(queue, command_buffers))
+        .expect("Unable to submit queue");
+
+    let marker = Box::new(WorkDoneMarker {
+        queue,
+        buffer,
+        closure,
+    });
+    let operation = wgc::resource::BufferMapOperation {
+        host: wgc::device::HostMap::Read,
+        callback: work_done_marker_mapped,
+        user_data: Box::into_raw(marker) as *mut u8,
+    };
+    gfx_select!(buffer => GLOBAL.buffer_map_async(buffer, 0 .. wgt::COPY_BUFFER_ALIGNMENT, operation))
+        .expect("Unable to map work done marker")
+}
+
+unsafe extern "C" fn work_done_marker_mapped(
+    status: wgc::resource::BufferMapAsyncStatus,
+    user_data: *mut u8,
+) {
+    let marker = Box::from_raw(user_data as *mut WorkDoneMarker);
+    if let wgc::resource::BufferMapAsyncStatus::Success = status {
+        gfx_select!(marker.buffer => GLOBAL.buffer_unmap(marker.buffer))
+            .expect("Unable to unmap work done marker");
+        MARKER_POOL
+            .lock()
+            .unwrap()
+            .entry(marker.queue)
+            .or_default()
+            .push(marker.buffer);
+    } else {
+        gfx_select!(marker.buffer => GLOBAL.buffer_drop(marker.buffer, false));
+    }
+    (marker.closure)(status);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuBufferMapAsync(
     buffer: id::BufferId,
@@ -315,21 +777,207 @@ pub unsafe extern "C" fn wgpuBufferMapAsync(
     offset: usize,
     size: usize,
     callback: native::WGPUBufferMapCallback,
-    user_data: *mut u8,
+    userdata: *mut std::os::raw::c_void,
 ) {
+    wgpuBufferMapAsync64(buffer, mode, offset as u64, size as u64, callback, userdata)
+}
+
+/// Same as `wgpuBufferMapAsync`, but takes 64-bit offsets so that 32-bit hosts can map
+/// ranges of buffers larger than their address space.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuBufferMapAsync64(
+    buffer: id::BufferId,
+    mode: native::WGPUMapModeFlags,
+    offset: u64,
+    size: u64,
+    callback: native::WGPUBufferMapCallback,
+    userdata: *mut std::os::raw::c_void,
+) {
+    let pending = PendingMap {
+        callback: callback.expect("Callback cannot be null"),
+        userdata,
+        mapping: MappedBuffer::new(0..0, false),
+    };
+
+    // The mode is a set of flags, of which exactly one of read or write access must be
+    // requested. Anything else, including unknown bits, is a validation error.
+    let host = match mode as crate::EnumConstant {
+        native::WGPUMapMode_Read => wgc::device::HostMap::Read,
+        native::WGPUMapMode_Write => wgc::device::HostMap::Write,
+        _ => {
+            log::error!("Invalid map mode: {:#x}", mode);
+            return pending.fire(native::WGPUBufferMapAsyncStatus_Error);
+        }
+    };
+    let range = match offset.checked_add(size) {
+        Some(end) => offset..end,
+        None => {
+            log::error!("Map range overflows: offset {}, size {}", offset, size);
+            return pending.fire(native::WGPUBufferMapAsyncStatus_Error);
+        }
+    };
+
+    // Registered up front, as another thread polling the device may resolve the mapping
+    // as soon as wgpu-core knows about it.
+    // An aborted mapping stays registered until wgpu-core resolves it, as wgpu-core would
+    // otherwise reject the new one by invoking its callback with the buffer locked.
+    let pending = match PENDING_MAPS.lock().unwrap().entry(buffer) {
+        Entry::Occupied(entry) => Err((pending, entry.get().is_aborted())),
+        Entry::Vacant(entry) => {
+            entry.insert(MapState::Waiting(PendingMap {
+                mapping: MappedBuffer::new(range.clone(), host == wgc::device::HostMap::Write),
+                ..pending
+            }));
+            Ok(())
+        }
+    };
+    if let Err((pending, aborted)) = pending {
+        if aborted {
+            log::error!(
+                "Buffer is still waiting for a mapping that was aborted, poll the device first"
+            );
+        } else {
+            log::error!("Buffer already has a pending mapping");
+        }
+        return pending.fire(native::WGPUBufferMapAsyncStatus_Error);
+    }
+
+    let user_data = Box::into_raw(Box::new(buffer));
     let operation = wgc::resource::BufferMapOperation {
-        host: match mode as crate::EnumConstant {
-            native::WGPUMapMode_Write => wgc::device::HostMap::Write,
-            native::WGPUMapMode_Read => wgc::device::HostMap::Read,
-            x => panic!("Unknown map mode: {}", x),
-        },
-        // TODO: Change wgpu-core to follow new API
-        callback: std::mem::transmute(callback.expect("Callback cannot be null")),
-        user_data,
+        host,
+        callback: buffer_map_callback,
+        user_data: user_data as *mut u8,
+    };
+    if let Err(err) = gfx_select!(buffer => GLOBAL.buffer_map_async(buffer, range, operation)) {
+        log::error!("Unable to map buffer: {:?}", err);
+        // wgpu-core doesn't invoke the callback for rejected requests.
+        drop(Box::from_raw(user_data));
+        // Released before firing, the callback may well map or unmap the buffer again.
+        let pending = PENDING_MAPS.lock().unwrap().remove(&buffer);
+        if let Some(MapState::Waiting(pending)) = pending {
+            pending.fire(native::WGPUBufferMapAsyncStatus_Error);
+        }
+    }
+}
+
+/// User callback of a mapping that wgpu-core hasn't resolved yet.
+struct PendingMap {
+    callback: unsafe extern "C" fn(native::WGPUBufferMapAsyncStatus, *mut std::os::raw::c_void),
+    userdata: *mut std::os::raw::c_void,
+    /// Becomes the buffer's mapping on success.
+    mapping: MappedBuffer,
+}
+
+// The userdata pointer is never dereferenced, only handed back to the callback.
+unsafe impl Send for PendingMap {}
+
+impl PendingMap {
+    unsafe fn fire(self, status: native::WGPUBufferMapAsyncStatus) {
+        (self.callback)(status, self.userdata);
+    }
+}
+
+/// Mapping that wgpu-core hasn't resolved yet.
+enum MapState {
+    Waiting(PendingMap),
+    /// The user already got its callback, but wgpu-core still waits to map the buffer.
+    Aborted,
+}
+
+impl MapState {
+    fn is_aborted(&self) -> bool {
+        match *self {
+            MapState::Waiting(_) => false,
+            MapState::Aborted => true,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PENDING_MAPS: Mutex<HashMap<id::BufferId, MapState>> =
+        Mutex::new(HashMap::new());
+}
+
+fn map_buffer_map_status(
+    status: wgc::resource::BufferMapAsyncStatus,
+) -> native::WGPUBufferMapAsyncStatus {
+    match status {
+        wgc::resource::BufferMapAsyncStatus::Success => native::WGPUBufferMapAsyncStatus_Success,
+        wgc::resource::BufferMapAsyncStatus::Error => native::WGPUBufferMapAsyncStatus_Error,
+        wgc::resource::BufferMapAsyncStatus::Unknown => native::WGPUBufferMapAsyncStatus_Unknown,
+        wgc::resource::BufferMapAsyncStatus::ContextLost => {
+            native::WGPUBufferMapAsyncStatus_DeviceLost
+        }
+    }
+}
+
+/// Called by wgpu-core once a mapping resolves, with a boxed `BufferId` as user data.
+unsafe extern "C" fn buffer_map_callback(
+    status: wgc::resource::BufferMapAsyncStatus,
+    user_data: *mut u8,
+) {
+    let buffer = *Box::from_raw(user_data as *mut id::BufferId);
+    let pending = PENDING_MAPS.lock().unwrap().remove(&buffer);
+    match pending {
+        Some(MapState::Waiting(mut pending)) => {
+            if let wgc::resource::BufferMapAsyncStatus::Success = status {
+                let mapping = mem::replace(&mut pending.mapping, MappedBuffer::new(0..0, false));
+                MAPPED_BUFFERS.lock().unwrap().insert(buffer, mapping);
+            }
+            pending.fire(map_buffer_map_status(status))
+        }
+        // The user already got its callback when the buffer was unmapped or destroyed.
+        // Release the mapping wgpu-core went on to establish regardless; this fails
+        // harmlessly if the buffer is gone.
+        Some(MapState::Aborted) | None => {
+            if let wgc::resource::BufferMapAsyncStatus::Success = status {
+                let _ = gfx_select!(buffer => GLOBAL.buffer_unmap(buffer));
+            }
+        }
+    }
+}
+
+/// Resolves a pending mapping of `buffer` early with `status`, returning whether there
+/// was one. It stays registered as aborted until wgpu-core resolves it.
+unsafe fn abort_pending_map(
+    buffer: id::BufferId,
+    status: native::WGPUBufferMapAsyncStatus,
+) -> bool {
+    let pending = match PENDING_MAPS.lock().unwrap().get_mut(&buffer) {
+        Some(state) => mem::replace(state, MapState::Aborted),
+        None => return false,
     };
+    if let MapState::Waiting(pending) = pending {
+        pending.fire(status);
+    }
+    true
+}
 
-    gfx_select!(buffer => GLOBAL.buffer_map_async(buffer, offset as u64 .. (offset + size) as u64, operation))
-        .expect("Unable to map buffer")
+/// Drops a device along with the information and internal resources kept for it.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuDeviceDrop(device: id::DeviceId) {
+    let buffers: Vec<_> = BUFFERS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, info)| info.device == device)
+        .map(|(&buffer, _)| buffer)
+        .collect();
+    for buffer in buffers {
+        release_buffer(buffer);
+    }
+    TEXTURES
+        .lock()
+        .unwrap()
+        .retain(|_, info| info.device != device);
+    SUBMISSIONS.lock().unwrap().remove(&device);
+    let markers = MARKER_POOL.lock().unwrap().remove(&device);
+    for buffer in markers.into_iter().flatten() {
+        gfx_select!(buffer => GLOBAL.buffer_drop(buffer, false));
+    }
+    crate::command::release_zero_buffer(device);
+    crate::blit::release_blit_resources(device);
+    gfx_select!(device => GLOBAL.device_drop(device));
 }
 
 #[no_mangle]
@@ -337,14 +985,178 @@ pub unsafe extern "C" fn wgpuDevicePoll(device: id::DeviceId, force_wait: bool)
     gfx_select!(device => GLOBAL.device_poll(device, force_wait)).expect("Unable to poll device")
 }
 
+/// Polls `device` until the submission `index` of its queue is done or `timeout_ms`
+/// milliseconds have passed, and returns whether the queue is idle afterwards. Backs off
+/// between polls rather than keeping a core busy.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuDevicePollUntil(
+    device: id::DeviceId,
+    index: native::WGPUSubmissionIndex,
+    timeout_ms: u32,
+) -> bool {
+    const MAX_BACKOFF: Duration = Duration::from_millis(1);
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms as u64);
+    let mut backoff = Duration::from_micros(10);
+    loop {
+        retire_untracked_submissions(device);
+        gfx_select!(device => GLOBAL.device_poll(device, false)).expect("Unable to poll device");
+        let (completed, idle) = SUBMISSIONS
+            .lock()
+            .unwrap()
+            .get(&device)
+            .map_or((0, true), |tracker| (tracker.completed, tracker.is_idle()));
+        let now = Instant::now();
+        if completed >= index || now >= deadline {
+            return idle;
+        }
+        thread::sleep(backoff.min(deadline - now));
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Submits a marker behind the untracked submissions of `queue`, if they aren't known
+/// to be done and no such marker is in flight already, so they retire once complete.
+unsafe fn retire_untracked_submissions(queue: id::QueueId) {
+    let untracked = {
+        let mut submissions = SUBMISSIONS.lock().unwrap();
+        let tracker = match submissions.get_mut(&queue) {
+            Some(tracker) => tracker,
+            None => return,
+        };
+        if tracker.retiring_untracked || tracker.untracked_completed == tracker.untracked_submitted
+        {
+            return;
+        }
+        tracker.retiring_untracked = true;
+        tracker.untracked_submitted
+    };
+    submit_with_marker(
+        queue,
+        &[],
+        Box::new(move |_| {
+            let mut submissions = SUBMISSIONS.lock().unwrap();
+            let tracker = submissions.entry(queue).or_default();
+            tracker.untracked_completed = tracker.untracked_completed.max(untracked);
+            tracker.retiring_untracked = false;
+        }),
+    );
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wgpuBufferGetMappedRange(
     buffer: id::BufferId,
     offset: usize,
     size: usize,
 ) -> *mut u8 {
-    gfx_select!(buffer => GLOBAL.buffer_get_mapped_range(buffer, offset as u64, NonZeroU64::new(size as u64)))
-        .expect("Unable to get mapped range").0
+    get_mapped_range(buffer, offset as u64, size as u64, true)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuBufferGetConstMappedRange(
+    buffer: id::BufferId,
+    offset: usize,
+    size: usize,
+) -> *const u8 {
+    get_mapped_range(buffer, offset as u64, size as u64, false)
+}
+
+/// Host-visible range of a mapped buffer, along with the subranges handed out from it
+/// so far. These must not overlap, as the returned pointers alias the same memory.
+struct MappedBuffer {
+    range: Range<wgt::BufferAddress>,
+    writable: bool,
+    handed_out: Vec<Range<wgt::BufferAddress>>,
+}
+
+impl MappedBuffer {
+    fn new(range: Range<wgt::BufferAddress>, writable: bool) -> Self {
+        Self {
+            range,
+            writable,
+            handed_out: Vec::new(),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref MAPPED_BUFFERS: Mutex<HashMap<id::BufferId, MappedBuffer>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Validates a mapped range request against the buffer's mapping. A `size` of 0 selects
+/// the rest of the mapping. Invalid requests are logged and return null, like they do
+/// in other WebGPU implementations.
+unsafe fn get_mapped_range(
+    buffer: id::BufferId,
+    offset: wgt::BufferAddress,
+    size: wgt::BufferAddress,
+    writable: bool,
+) -> *mut u8 {
+    match validate_mapped_range(buffer, offset, size, writable) {
+        Ok(range) => {
+            match gfx_select!(buffer => GLOBAL.buffer_get_mapped_range(buffer, range.start, NonZeroU64::new(range.end - range.start)))
+            {
+                Ok((data, _)) => data,
+                Err(err) => {
+                    log::error!("Unable to get mapped range: {:?}", err);
+                    release_mapped_range(buffer, &range);
+                    ptr::null_mut()
+                }
+            }
+        }
+        Err(message) => {
+            log::error!("{}", message);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Records the requested range as handed out if it is valid, reserving it against
+/// concurrent requests until wgpu-core either hands it out or refuses it.
+fn validate_mapped_range(
+    buffer: id::BufferId,
+    offset: wgt::BufferAddress,
+    size: wgt::BufferAddress,
+    writable: bool,
+) -> Result<Range<wgt::BufferAddress>, String> {
+    let mut mapped_buffers = MAPPED_BUFFERS.lock().unwrap();
+    let mapped = mapped_buffers
+        .get_mut(&buffer)
+        .ok_or_else(|| "Buffer is not mapped".to_string())?;
+    if writable && !mapped.writable {
+        return Err("Buffer is mapped for reading, use wgpuBufferGetConstMappedRange".to_string());
+    }
+    let end = match size {
+        0 => mapped.range.end,
+        size => offset.saturating_add(size),
+    };
+    if offset < mapped.range.start || end > mapped.range.end || offset > end {
+        return Err(format!(
+            "Mapped range {:?} is out of bounds of the mapping {:?}",
+            offset..end,
+            mapped.range
+        ));
+    }
+    if let Some(other) = mapped
+        .handed_out
+        .iter()
+        .find(|other| offset < other.end && other.start < end)
+    {
+        return Err(format!(
+            "Mapped range {:?} overlaps the previously returned range {:?}",
+            offset..end,
+            other
+        ));
+    }
+    mapped.handed_out.push(offset..end);
+    Ok(offset..end)
+}
+
+/// Forgets a range recorded by `validate_mapped_range` that wgpu-core refused to hand out.
+fn release_mapped_range(buffer: id::BufferId, range: &Range<wgt::BufferAddress>) {
+    if let Some(mapped) = MAPPED_BUFFERS.lock().unwrap().get_mut(&buffer) {
+        mapped.handed_out.retain(|other| other != range);
+    }
 }
 
 #[no_mangle]
@@ -352,16 +1164,35 @@ pub unsafe extern "C" fn wgpuDeviceCreateRenderPipeline(
     device: id::DeviceId,
     descriptor: &native::WGPURenderPipelineDescriptor,
 ) -> id::RenderPipelineId {
+    validate_sample_count(descriptor.multisample.count);
+    let vertex_extras = follow_chain!(
+        get_stage_extras(&descriptor.vertex,
+        WGPUSType_ProgrammableStageExtras => native::WGPUProgrammableStageExtras)
+    );
+    let vertex_stage = map_programmable_stage(
+        device,
+        descriptor.vertex.module,
+        descriptor.vertex.entryPoint,
+        vertex_extras,
+    );
+    let fragment_stage = descriptor.fragment.as_ref().map(|fragment| {
+        let fragment_extras = follow_chain!(
+            get_stage_extras(fragment,
+            WGPUSType_ProgrammableStageExtras => native::WGPUProgrammableStageExtras)
+        );
+        map_programmable_stage(
+            device,
+            fragment.module,
+            fragment.entryPoint,
+            fragment_extras,
+        )
+    });
+
     let desc = wgc::pipeline::RenderPipelineDescriptor {
         label: OwnedLabel::new(descriptor.label).into_cow(),
         layout: Some(descriptor.layout),
         vertex: wgc::pipeline::VertexState {
-            stage: wgc::pipeline::ProgrammableStageDescriptor {
-                module: descriptor.vertex.module,
-                entry_point: OwnedLabel::new(descriptor.vertex.entryPoint)
-                    .into_cow()
-                    .expect("Entry point not provided"),
-            },
+            stage: vertex_stage,
             buffers: Cow::Owned(
                 make_slice(
                     descriptor.vertex.buffers,
@@ -416,13 +1247,9 @@ pub unsafe extern "C" fn wgpuDeviceCreateRenderPipeline(
         fragment: descriptor
             .fragment
             .as_ref()
-            .map(|fragment| wgc::pipeline::FragmentState {
-                stage: wgc::pipeline::ProgrammableStageDescriptor {
-                    module: fragment.module,
-                    entry_point: OwnedLabel::new(fragment.entryPoint)
-                        .into_cow()
-                        .expect("Entry point not provided"),
-                },
+            .zip(fragment_stage)
+            .map(|(fragment, stage)| wgc::pipeline::FragmentState {
+                stage,
                 targets: Cow::Owned(
                     make_slice(fragment.targets, fragment.targetCount as usize)
                         .iter()
@@ -525,16 +1352,370 @@ pub extern "C" fn wgpuDeviceCreateTexture(
         format: map_texture_format(descriptor.format).expect("Texture format must be provided"),
         usage: wgt::TextureUsage::from_bits(descriptor.usage).expect("Invalid texture usage"),
     };
+    validate_sample_count(desc.sample_count);
+    let (block_width, block_height) = desc.format.describe().block_dimensions;
+    if (block_width, block_height) != (1, 1) {
+        if desc.dimension != wgt::TextureDimension::D2 {
+            panic!(
+                "Textures with compressed format {:?} must be 2D",
+                desc.format
+            );
+        }
+        if desc.size.width % block_width as u32 != 0 || desc.size.height % block_height as u32 != 0
+        {
+            panic!(
+                "Size of textures with compressed format {:?} must be a multiple of {}x{}",
+                desc.format, block_width, block_height
+            );
+        }
+    }
+    if desc.sample_count > 1 {
+        if desc.dimension != wgt::TextureDimension::D2 || desc.size.depth_or_array_layers != 1 {
+            panic!("Multisampled textures must be 2D with a single array layer");
+        }
+        if desc.mip_level_count != 1 {
+            panic!("Multisampled textures must have a single mip level");
+        }
+        if desc.usage.contains(wgt::TextureUsage::STORAGE) {
+            panic!("Multisampled textures cannot have STORAGE usage");
+        }
+    }
 
-    check_error(gfx_select!(device => GLOBAL.device_create_texture(device, &desc, PhantomData)))
+    let id = check_error(
+        gfx_select!(device => GLOBAL.device_create_texture(device, &desc, PhantomData)),
+    );
+    TEXTURES.lock().unwrap().insert(
+        id,
+        TextureInfo {
+            device,
+            desc: desc.map_label(|_| ()),
+        },
+    );
+    id
+}
+
+/// Creates a texture and uploads `data` to all of its mip levels and array layers.
+///
+/// The data is expected to hold each layer in order, each of them with all its mip
+/// levels tightly packed. 3D textures are a single layer of all depth slices. Mip levels
+/// of block compressed formats are rounded up to whole blocks.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuDeviceCreateTextureWithData(
+    device: id::DeviceId,
+    descriptor: &native::WGPUTextureDescriptor,
+    data: *const u8,
+    data_size: usize,
+) -> id::TextureId {
+    let texture = wgpuDeviceCreateTexture(device, descriptor);
+    let info = texture_info(texture).expect("Texture was just created");
+    let data = make_slice(data, data_size);
+    let format_info = info.desc.format.describe();
+    let (block_width, block_height) = (
+        format_info.block_dimensions.0 as u32,
+        format_info.block_dimensions.1 as u32,
+    );
+
+    let mut offset = 0;
+    for layer in 0..info.array_layer_count() {
+        for mip_level in 0..info.desc.mip_level_count {
+            let mip_size = info.mip_level_size(mip_level);
+            let width_blocks = (mip_size.width + block_width - 1) / block_width;
+            let height_blocks = (mip_size.height + block_height - 1) / block_height;
+            let depth = match info.desc.dimension {
+                wgt::TextureDimension::D3 => mip_size.depth_or_array_layers,
+                _ => 1,
+            };
+            let bytes_per_row = width_blocks * format_info.block_size as u32;
+            let end = offset as u64 + bytes_per_row as u64 * height_blocks as u64 * depth as u64;
+            if end > data.len() as u64 {
+                panic!(
+                    "Texture data is too small: {} bytes provided, at least {} needed",
+                    data.len(),
+                    end
+                );
+            }
+            let end = end as usize;
+
+            gfx_select!(device => GLOBAL.queue_write_texture(
+            device,
+            &wgt::ImageCopyTexture {
+                texture,
+                mip_level,
+                origin: wgt::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: layer,
+                },
+            },
+            &data[offset..end],
+            &wgt::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(bytes_per_row),
+                rows_per_image: NonZeroU32::new(height_blocks * block_height),
+            },
+            &wgt::Extent3d {
+                width: width_blocks * block_width,
+                height: height_blocks * block_height,
+                depth_or_array_layers: depth,
+            }))
+            .expect("Unable to write texture data");
+            offset = end;
+        }
+    }
+    texture
+}
+
+/// Reads back a region of a texture's mip level, the whole level if `region` is null.
+///
+/// The texture is copied into a staging buffer on submission, and the callback gets the
+/// tightly packed texels once the device is polled after the copy is done. The data is
+/// only valid during the callback.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuTextureReadAsync(
+    texture: id::TextureId,
+    mip_level: u32,
+    region: Option<&native::WGPUTextureRegion>,
+    callback: native::WGPUTextureReadCallback,
+    userdata: *mut std::os::raw::c_void,
+) {
+    let callback = callback.expect("Callback cannot be null");
+    let info = match texture_info(texture) {
+        Some(info) => info,
+        None => {
+            return callback(
+                native::WGPUBufferMapAsyncStatus_Error,
+                ptr::null(),
+                0,
+                userdata,
+            )
+        }
+    };
+    let (origin, size) = match validate_texture_read(&info, mip_level, region) {
+        Ok(region) => region,
+        Err(message) => {
+            log::error!("{}", message);
+            return callback(
+                native::WGPUBufferMapAsyncStatus_Error,
+                ptr::null(),
+                0,
+                userdata,
+            );
+        }
+    };
+
+    let format_info = info.desc.format.describe();
+    let unpadded_bytes_per_row = size.width * format_info.block_size as u32;
+    let padded_bytes_per_row = match unpadded_bytes_per_row % wgt::COPY_BYTES_PER_ROW_ALIGNMENT {
+        0 => unpadded_bytes_per_row,
+        rem => unpadded_bytes_per_row + wgt::COPY_BYTES_PER_ROW_ALIGNMENT - rem,
+    };
+    let rows = size.height * size.depth_or_array_layers;
+    let buffer_size = padded_bytes_per_row as wgt::BufferAddress * rows as wgt::BufferAddress;
+
+    let device = info.device;
+    let buffer = check_error(gfx_select!(device => GLOBAL.device_create_buffer(
+        device,
+        &wgt::BufferDescriptor {
+            label: Some(Cow::Borrowed("texture readback")),
+            size: buffer_size,
+            usage: wgt::BufferUsage::MAP_READ | wgt::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        },
+        PhantomData
+    )));
+    let encoder = check_error(gfx_select!(device => GLOBAL.device_create_command_encoder(
+        device,
+        &wgt::CommandEncoderDescriptor { label: None },
+        PhantomData
+    )));
+    let copied = gfx_select!(encoder => GLOBAL.command_encoder_copy_texture_to_buffer(
+        encoder,
+        &wgt::ImageCopyTexture {
+            texture,
+            mip_level,
+            origin,
+        },
+        &wgt::ImageCopyBuffer {
+            buffer,
+            layout: wgt::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: NonZeroU32::new(size.height),
+            },
+        },
+        &size));
+    if let Err(err) = copied {
+        log::error!("Unable to copy texture for reading: {:?}", err);
+        gfx_select!(encoder => GLOBAL.command_encoder_drop(encoder));
+        gfx_select!(buffer => GLOBAL.buffer_drop(buffer, false));
+        return callback(
+            native::WGPUBufferMapAsyncStatus_Error,
+            ptr::null(),
+            0,
+            userdata,
+        );
+    }
+    let command_buffer = check_error(gfx_select!(encoder => GLOBAL.command_encoder_finish(
+        encoder,
+        &wgt::CommandBufferDescriptor { label: None }
+    )));
+    gfx_select!(device => GLOBAL.queue_submit(device, &[command_buffer]))
+        .expect("Unable to submit queue");
+
+    let read = Box::into_raw(Box::new(TextureRead {
+        buffer,
+        unpadded_bytes_per_row,
+        padded_bytes_per_row,
+        rows,
+        callback,
+        userdata,
+    }));
+    let operation = wgc::resource::BufferMapOperation {
+        host: wgc::device::HostMap::Read,
+        callback: texture_read_mapped,
+        user_data: read as *mut u8,
+    };
+    if let Err(err) =
+        gfx_select!(buffer => GLOBAL.buffer_map_async(buffer, 0..buffer_size, operation))
+    {
+        log::error!("Unable to map texture readback buffer: {:?}", err);
+        drop(Box::from_raw(read));
+        gfx_select!(buffer => GLOBAL.buffer_drop(buffer, false));
+        callback(
+            native::WGPUBufferMapAsyncStatus_Error,
+            ptr::null(),
+            0,
+            userdata,
+        );
+    }
+}
+
+/// Checks a texture read up front, so that invalid reads fail through the callback rather
+/// than in the copy. Returns the origin and size of the region to read.
+fn validate_texture_read(
+    info: &TextureInfo,
+    mip_level: u32,
+    region: Option<&native::WGPUTextureRegion>,
+) -> Result<(wgt::Origin3d, wgt::Extent3d), String> {
+    let format = info.desc.format;
+    if !info.desc.usage.contains(wgt::TextureUsage::COPY_SRC) {
+        return Err("Reading back a texture requires COPY_SRC usage".to_string());
+    }
+    if format.describe().block_dimensions != (1, 1) {
+        return Err(format!(
+            "Reading back compressed texture format {:?} is not supported",
+            format
+        ));
+    }
+    if let wgt::TextureFormat::Depth24Plus | wgt::TextureFormat::Depth24PlusStencil8 = format {
+        return Err(format!(
+            "Texture format {:?} can't be copied to a buffer",
+            format
+        ));
+    }
+    if mip_level >= info.desc.mip_level_count {
+        return Err(format!(
+            "Mip level {} is out of range, the texture has {} levels",
+            mip_level, info.desc.mip_level_count
+        ));
+    }
+
+    let mip_size = info.mip_level_size(mip_level);
+    let (origin, size) = match region {
+        Some(region) => (
+            crate::command::map_origin3d(&region.origin),
+            crate::command::map_extent3d(&region.size),
+        ),
+        None => (wgt::Origin3d::ZERO, mip_size),
+    };
+    if size.width == 0 || size.height == 0 || size.depth_or_array_layers == 0 {
+        return Err("Texture read region is empty".to_string());
+    }
+    let fits = |start: u32, size: u32, extent: u32| {
+        start.checked_add(size).map_or(false, |end| end <= extent)
+    };
+    if !fits(origin.x, size.width, mip_size.width)
+        || !fits(origin.y, size.height, mip_size.height)
+        || !fits(
+            origin.z,
+            size.depth_or_array_layers,
+            mip_size.depth_or_array_layers,
+        )
+    {
+        return Err(format!(
+            "Texture read region at {:?} of size {:?} is out of bounds of mip level {} of size {:?}",
+            origin, size, mip_level, mip_size
+        ));
+    }
+    Ok((origin, size))
+}
+
+/// Staging buffer of a texture readback, along with its row layout.
+struct TextureRead {
+    buffer: id::BufferId,
+    unpadded_bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    rows: u32,
+    callback: unsafe extern "C" fn(
+        native::WGPUBufferMapAsyncStatus,
+        *const u8,
+        usize,
+        *mut std::os::raw::c_void,
+    ),
+    userdata: *mut std::os::raw::c_void,
+}
+
+unsafe extern "C" fn texture_read_mapped(
+    status: wgc::resource::BufferMapAsyncStatus,
+    user_data: *mut u8,
+) {
+    let read = *Box::from_raw(user_data as *mut TextureRead);
+    let status = map_buffer_map_status(status);
+    if status != native::WGPUBufferMapAsyncStatus_Success {
+        (read.callback)(status, ptr::null(), 0, read.userdata);
+        gfx_select!(read.buffer => GLOBAL.buffer_drop(read.buffer, false));
+        return;
+    }
+
+    let padded_size = read.padded_bytes_per_row as usize * read.rows as usize;
+    let padded = make_slice(
+        gfx_select!(read.buffer => GLOBAL.buffer_get_mapped_range(read.buffer, 0, NonZeroU64::new(padded_size as u64)))
+            .expect("Unable to get mapped range")
+            .0,
+        padded_size,
+    );
+    let data = padded
+        .chunks(read.padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..read.unpadded_bytes_per_row as usize])
+        .cloned()
+        .collect::<Vec<u8>>();
+    gfx_select!(read.buffer => GLOBAL.buffer_unmap(read.buffer))
+        .expect("Unable to unmap texture readback buffer");
+    gfx_select!(read.buffer => GLOBAL.buffer_drop(read.buffer, false));
+
+    (read.callback)(status, data.as_ptr(), data.len(), read.userdata);
+}
+
+/// WebGPU only allows single-sampled and 4x multisampled textures and pipelines.
+fn validate_sample_count(count: u32) {
+    if count != 1 && count != 4 {
+        panic!("Invalid sample count {}, must be 1 or 4", count);
+    }
 }
 
 #[no_mangle]
 pub extern "C" fn wgpu_texture_destroy(texture_id: id::TextureId) {
+    TEXTURES.lock().unwrap().remove(&texture_id);
     gfx_select!(texture_id => GLOBAL.texture_destroy(texture_id))
         .expect("Failed to destroy texture");
 }
 
+#[no_mangle]
+pub extern "C" fn wgpuTextureDrop(texture: id::TextureId) {
+    TEXTURES.lock().unwrap().remove(&texture);
+    gfx_select!(texture => GLOBAL.texture_drop(texture, false));
+}
+
 #[no_mangle]
 pub extern "C" fn wgpuDeviceCreateSampler(
     device: id::DeviceId,
@@ -564,7 +1745,16 @@ pub extern "C" fn wgpuDeviceCreateSampler(
 }
 
 #[no_mangle]
-pub extern "C" fn wgpuBufferUnmap(buffer_id: id::BufferId) {
+pub unsafe extern "C" fn wgpuBufferUnmap(buffer_id: id::BufferId) {
+    // wgpu-core can't cancel a pending mapping, so it is left to resolve and is then
+    // unmapped by `buffer_map_callback`.
+    if abort_pending_map(
+        buffer_id,
+        native::WGPUBufferMapAsyncStatus_UnmappedBeforeCallback,
+    ) {
+        return;
+    }
+    MAPPED_BUFFERS.lock().unwrap().remove(&buffer_id);
     gfx_select!(buffer_id => GLOBAL.buffer_unmap(buffer_id)).expect("Unable to unmap buffer")
 }
 
@@ -621,6 +1811,17 @@ map_enum!(
     Mailbox,
     Fifo
 );
+map_enum!(
+    map_pipeline_statistic_name,
+    WGPUPipelineStatisticName,
+    wgt::PipelineStatisticsTypes,
+    "Unknown pipeline statistic name",
+    VertexShaderInvocations:VERTEX_SHADER_INVOCATIONS,
+    ClipperInvocations:CLIPPER_INVOCATIONS,
+    ClipperPrimitivesOut:CLIPPER_PRIMITIVES_OUT,
+    FragmentShaderInvocations:FRAGMENT_SHADER_INVOCATIONS,
+    ComputeShaderInvocations:COMPUTE_SHADER_INVOCATIONS
+);
 map_enum!(
     map_primitive_topology,
     WGPUPrimitiveTopology,
@@ -727,8 +1928,9 @@ pub fn map_texture_dimension(value: crate::EnumConstant) -> wgt::TextureDimensio
     }
 }
 
+/// Compressed formats additionally require their `TEXTURE_COMPRESSION_*` feature, which
+/// wgpu-core checks on texture creation.
 pub fn map_texture_format(value: crate::EnumConstant) -> Option<wgt::TextureFormat> {
-    // TODO: Add support for BC formats
     match value {
         native::WGPUTextureFormat_R8Unorm => Some(wgt::TextureFormat::R8Unorm),
         native::WGPUTextureFormat_R8Snorm => Some(wgt::TextureFormat::R8Snorm),
@@ -769,6 +1971,90 @@ pub fn map_texture_format(value: crate::EnumConstant) -> Option<wgt::TextureForm
         native::WGPUTextureFormat_Depth24PlusStencil8 => {
             Some(wgt::TextureFormat::Depth24PlusStencil8)
         }
+        native::WGPUTextureFormat_BC1RGBAUnorm => Some(wgt::TextureFormat::Bc1RgbaUnorm),
+        native::WGPUTextureFormat_BC1RGBAUnormSrgb => Some(wgt::TextureFormat::Bc1RgbaUnormSrgb),
+        native::WGPUTextureFormat_BC2RGBAUnorm => Some(wgt::TextureFormat::Bc2RgbaUnorm),
+        native::WGPUTextureFormat_BC2RGBAUnormSrgb => Some(wgt::TextureFormat::Bc2RgbaUnormSrgb),
+        native::WGPUTextureFormat_BC3RGBAUnorm => Some(wgt::TextureFormat::Bc3RgbaUnorm),
+        native::WGPUTextureFormat_BC3RGBAUnormSrgb => Some(wgt::TextureFormat::Bc3RgbaUnormSrgb),
+        native::WGPUTextureFormat_BC4RUnorm => Some(wgt::TextureFormat::Bc4RUnorm),
+        native::WGPUTextureFormat_BC4RSnorm => Some(wgt::TextureFormat::Bc4RSnorm),
+        native::WGPUTextureFormat_BC5RGUnorm => Some(wgt::TextureFormat::Bc5RgUnorm),
+        native::WGPUTextureFormat_BC5RGSnorm => Some(wgt::TextureFormat::Bc5RgSnorm),
+        native::WGPUTextureFormat_BC6HRGBUfloat => Some(wgt::TextureFormat::Bc6hRgbUfloat),
+        native::WGPUTextureFormat_BC6HRGBFloat => Some(wgt::TextureFormat::Bc6hRgbSfloat),
+        native::WGPUTextureFormat_BC7RGBAUnorm => Some(wgt::TextureFormat::Bc7RgbaUnorm),
+        native::WGPUTextureFormat_BC7RGBAUnormSrgb => Some(wgt::TextureFormat::Bc7RgbaUnormSrgb),
+        native::WGPUTextureFormat_ETC2RGB8Unorm => Some(wgt::TextureFormat::Etc2RgbUnorm),
+        native::WGPUTextureFormat_ETC2RGB8UnormSrgb => Some(wgt::TextureFormat::Etc2RgbUnormSrgb),
+        native::WGPUTextureFormat_ETC2RGB8A1Unorm => Some(wgt::TextureFormat::Etc2RgbA1Unorm),
+        native::WGPUTextureFormat_ETC2RGB8A1UnormSrgb => {
+            Some(wgt::TextureFormat::Etc2RgbA1UnormSrgb)
+        }
+        native::WGPUTextureFormat_ETC2RGBA8Unorm => Some(wgt::TextureFormat::Etc2RgbA8Unorm),
+        native::WGPUTextureFormat_ETC2RGBA8UnormSrgb => {
+            Some(wgt::TextureFormat::Etc2RgbA8UnormSrgb)
+        }
+        native::WGPUTextureFormat_EACR11Unorm => Some(wgt::TextureFormat::EacRUnorm),
+        native::WGPUTextureFormat_EACR11Snorm => Some(wgt::TextureFormat::EacRSnorm),
+        native::WGPUTextureFormat_EACRG11Unorm => Some(wgt::TextureFormat::EacRgUnorm),
+        native::WGPUTextureFormat_EACRG11Snorm => Some(wgt::TextureFormat::EacRgSnorm),
+        native::WGPUTextureFormat_ASTC4x4Unorm => Some(wgt::TextureFormat::Astc4x4RgbaUnorm),
+        native::WGPUTextureFormat_ASTC4x4UnormSrgb => {
+            Some(wgt::TextureFormat::Astc4x4RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC5x4Unorm => Some(wgt::TextureFormat::Astc5x4RgbaUnorm),
+        native::WGPUTextureFormat_ASTC5x4UnormSrgb => {
+            Some(wgt::TextureFormat::Astc5x4RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC5x5Unorm => Some(wgt::TextureFormat::Astc5x5RgbaUnorm),
+        native::WGPUTextureFormat_ASTC5x5UnormSrgb => {
+            Some(wgt::TextureFormat::Astc5x5RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC6x5Unorm => Some(wgt::TextureFormat::Astc6x5RgbaUnorm),
+        native::WGPUTextureFormat_ASTC6x5UnormSrgb => {
+            Some(wgt::TextureFormat::Astc6x5RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC6x6Unorm => Some(wgt::TextureFormat::Astc6x6RgbaUnorm),
+        native::WGPUTextureFormat_ASTC6x6UnormSrgb => {
+            Some(wgt::TextureFormat::Astc6x6RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC8x5Unorm => Some(wgt::TextureFormat::Astc8x5RgbaUnorm),
+        native::WGPUTextureFormat_ASTC8x5UnormSrgb => {
+            Some(wgt::TextureFormat::Astc8x5RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC8x6Unorm => Some(wgt::TextureFormat::Astc8x6RgbaUnorm),
+        native::WGPUTextureFormat_ASTC8x6UnormSrgb => {
+            Some(wgt::TextureFormat::Astc8x6RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC8x8Unorm => Some(wgt::TextureFormat::Astc8x8RgbaUnorm),
+        native::WGPUTextureFormat_ASTC8x8UnormSrgb => {
+            Some(wgt::TextureFormat::Astc8x8RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC10x5Unorm => Some(wgt::TextureFormat::Astc10x5RgbaUnorm),
+        native::WGPUTextureFormat_ASTC10x5UnormSrgb => {
+            Some(wgt::TextureFormat::Astc10x5RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC10x6Unorm => Some(wgt::TextureFormat::Astc10x6RgbaUnorm),
+        native::WGPUTextureFormat_ASTC10x6UnormSrgb => {
+            Some(wgt::TextureFormat::Astc10x6RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC10x8Unorm => Some(wgt::TextureFormat::Astc10x8RgbaUnorm),
+        native::WGPUTextureFormat_ASTC10x8UnormSrgb => {
+            Some(wgt::TextureFormat::Astc10x8RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC10x10Unorm => Some(wgt::TextureFormat::Astc10x10RgbaUnorm),
+        native::WGPUTextureFormat_ASTC10x10UnormSrgb => {
+            Some(wgt::TextureFormat::Astc10x10RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC12x10Unorm => Some(wgt::TextureFormat::Astc12x10RgbaUnorm),
+        native::WGPUTextureFormat_ASTC12x10UnormSrgb => {
+            Some(wgt::TextureFormat::Astc12x10RgbaUnormSrgb)
+        }
+        native::WGPUTextureFormat_ASTC12x12Unorm => Some(wgt::TextureFormat::Astc12x12RgbaUnorm),
+        native::WGPUTextureFormat_ASTC12x12UnormSrgb => {
+            Some(wgt::TextureFormat::Astc12x12RgbaUnormSrgb)
+        }
         _ => None,
     }
 }
@@ -1,9 +1,12 @@
 use std::{borrow::Cow, marker::PhantomData, sync::Arc};
 use wgc::id;
 
+pub mod belt;
+pub mod blit;
 pub mod command;
 pub mod device;
 pub mod logging;
+pub mod shader;
 
 pub mod native {
     #![allow(non_upper_case_globals)]
@@ -0,0 +1,363 @@
+use crate::{check_error, make_slice, map_enum, native, OwnedLabel, GLOBAL};
+use std::{
+    collections::HashMap,
+    ffi::{CStr, CString},
+    marker::PhantomData,
+    ptr,
+    sync::Mutex,
+};
+use wgc::{gfx_select, id, pipeline::ShaderModuleSource};
+
+lazy_static::lazy_static! {
+    static ref SHADER_SOURCES: Mutex<HashMap<id::ShaderModuleId, ShaderSourceCopy>> =
+        Mutex::new(HashMap::new());
+    /// Specialized copies of each module, keyed by their constant overrides, so that
+    /// pipelines sharing them don't parse the source again.
+    static ref SPECIALIZED_MODULES: Mutex<HashMap<id::ShaderModuleId, HashMap<Vec<(String, u64)>, id::ShaderModuleId>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Copy of the source a shader module was created from, kept around so that
+/// pipelines can re-specialize the module with overridden constants.
+pub(crate) enum ShaderSourceCopy {
+    Wgsl(String),
+    SpirV(Vec<u32>),
+}
+
+impl ShaderSourceCopy {
+    pub(crate) fn new(source: &ShaderModuleSource) -> Option<Self> {
+        match source {
+            ShaderModuleSource::Wgsl(code) => Some(Self::Wgsl(code.to_string())),
+            ShaderModuleSource::SpirV(words) => Some(Self::SpirV(words.to_vec())),
+            ShaderModuleSource::Naga(_) => None,
+        }
+    }
+
+    pub(crate) fn register(self, module: id::ShaderModuleId) {
+        SHADER_SOURCES.lock().unwrap().insert(module, self);
+    }
+}
+
+/// Forgets the source copy of a shader module that is being dropped, along with its
+/// specialized copies.
+pub(crate) fn release_shader_source(module: id::ShaderModuleId) {
+    SHADER_SOURCES.lock().unwrap().remove(&module);
+    let specialized = SPECIALIZED_MODULES.lock().unwrap().remove(&module);
+    for (_, specialized) in specialized.into_iter().flatten() {
+        gfx_select!(specialized => GLOBAL.shader_module_drop(specialized));
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum SpecializationError {
+    SourceUnavailable,
+    Parse(String),
+    UnknownConstant(String),
+    NotScalar(String),
+}
+
+/// Returns a copy of `module` with the given module-scope constants overridden. Copies
+/// are kept until the module is dropped.
+///
+/// Constants are matched by name, which covers named WGSL constants as well as SPIR-V
+/// specialization constants carrying an `OpName`. Numeric keys match the specialization
+/// ID of constants the frontend records it for.
+pub(crate) unsafe fn specialize_shader_module(
+    device: id::DeviceId,
+    module: id::ShaderModuleId,
+    constants: &[native::WGPUConstantEntry],
+) -> Result<id::ShaderModuleId, SpecializationError> {
+    let overrides: Vec<_> = constants
+        .iter()
+        .map(|entry| {
+            (
+                CStr::from_ptr(entry.key).to_string_lossy().into_owned(),
+                entry.value.to_bits(),
+            )
+        })
+        .collect();
+    if let Some(&specialized) = SPECIALIZED_MODULES
+        .lock()
+        .unwrap()
+        .get(&module)
+        .and_then(|modules| modules.get(&overrides))
+    {
+        return Ok(specialized);
+    }
+
+    let mut naga_module = match SHADER_SOURCES.lock().unwrap().get(&module) {
+        Some(ShaderSourceCopy::Wgsl(code)) => naga::front::wgsl::parse_str(code)
+            .map_err(|e| SpecializationError::Parse(format!("{:?}", e)))?,
+        Some(ShaderSourceCopy::SpirV(words)) => {
+            naga::front::spv::Parser::new(words.iter().cloned(), &Default::default())
+                .parse()
+                .map_err(|e| SpecializationError::Parse(format!("{:?}", e)))?
+        }
+        None => return Err(SpecializationError::SourceUnavailable),
+    };
+
+    for entry in constants {
+        let key = CStr::from_ptr(entry.key).to_string_lossy();
+        let spec_id = key.parse::<u32>().ok();
+        let handles: Vec<_> = naga_module
+            .constants
+            .iter()
+            .filter(|(_, constant)| match spec_id {
+                Some(spec_id) => constant.specialization == Some(spec_id),
+                None => constant.name.as_deref() == Some(&*key),
+            })
+            .map(|(handle, _)| handle)
+            .collect();
+        if handles.is_empty() {
+            return Err(SpecializationError::UnknownConstant(key.into_owned()));
+        }
+        for handle in handles {
+            match naga_module.constants.get_mut(handle).inner {
+                naga::ConstantInner::Scalar { ref mut value, .. } => {
+                    *value = match *value {
+                        naga::ScalarValue::Sint(_) => naga::ScalarValue::Sint(entry.value as i64),
+                        naga::ScalarValue::Uint(_) => naga::ScalarValue::Uint(entry.value as u64),
+                        naga::ScalarValue::Float(_) => naga::ScalarValue::Float(entry.value),
+                        naga::ScalarValue::Bool(_) => naga::ScalarValue::Bool(entry.value != 0.0),
+                    }
+                }
+                naga::ConstantInner::Composite { .. } => {
+                    return Err(SpecializationError::NotScalar(key.into_owned()))
+                }
+            }
+        }
+    }
+
+    let desc = wgc::pipeline::ShaderModuleDescriptor {
+        label: None,
+        flags: wgt::ShaderFlags::VALIDATION,
+    };
+    let specialized = check_error(gfx_select!(device => GLOBAL.device_create_shader_module(
+        device,
+        &desc,
+        ShaderModuleSource::Naga(naga_module),
+        PhantomData
+    )));
+    SPECIALIZED_MODULES
+        .lock()
+        .unwrap()
+        .entry(module)
+        .or_default()
+        .insert(overrides, specialized);
+    Ok(specialized)
+}
+
+pub enum ShaderSourceKind {
+    Wgsl,
+    SpirV,
+    Glsl,
+}
+
+pub enum ShaderTargetKind {
+    SpirV,
+    Glsl,
+    Wgsl,
+}
+
+struct TranslationError {
+    status: native::WGPUShaderTranslationStatus,
+    message: String,
+    /// 1-based location of the error in the source, 0 when the frontend doesn't report it.
+    line: u32,
+    column: u32,
+}
+
+impl TranslationError {
+    fn new(status: native::WGPUShaderTranslationStatus, message: String) -> Self {
+        Self {
+            status,
+            message,
+            line: 0,
+            column: 0,
+        }
+    }
+
+    fn from_wgsl(error: naga::front::wgsl::ParseError) -> Self {
+        Self {
+            line: error.line as u32,
+            // naga reports the number of bytes on the line before the error.
+            column: if error.line == 0 {
+                0
+            } else {
+                error.pos as u32 + 1
+            },
+            ..Self::new(
+                native::WGPUShaderTranslationStatus_ParseError,
+                error.to_string(),
+            )
+        }
+    }
+}
+
+/// Translates a shader without requiring an adapter or a device.
+///
+/// The returned data and message are owned by the library and must be released
+/// with `wgpuShaderTranslationFree`.
+#[no_mangle]
+pub unsafe extern "C" fn wgpuTranslateShader(
+    source: &native::WGPUShaderTranslationSource,
+    source_kind: native::WGPUShaderSourceKind,
+    target_kind: native::WGPUShaderTargetKind,
+) -> native::WGPUShaderTranslation {
+    let result = translate_shader(
+        source,
+        map_shader_source_kind(source_kind),
+        map_shader_target_kind(target_kind),
+    );
+
+    match result {
+        Ok(data) => {
+            let data = data.into_boxed_slice();
+            native::WGPUShaderTranslation {
+                status: native::WGPUShaderTranslationStatus_Success,
+                dataSize: data.len(),
+                data: Box::into_raw(data) as *const u8,
+                message: ptr::null(),
+                line: 0,
+                column: 0,
+            }
+        }
+        Err(error) => native::WGPUShaderTranslation {
+            status: error.status,
+            data: ptr::null(),
+            dataSize: 0,
+            message: CString::new(error.message).unwrap_or_default().into_raw(),
+            line: error.line,
+            column: error.column,
+        },
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn wgpuShaderTranslationFree(translation: native::WGPUShaderTranslation) {
+    if !translation.data.is_null() {
+        drop(Box::from_raw(std::slice::from_raw_parts_mut(
+            translation.data as *mut u8,
+            translation.dataSize,
+        )));
+    }
+    if !translation.message.is_null() {
+        drop(CString::from_raw(translation.message as *mut _));
+    }
+}
+
+unsafe fn translate_shader(
+    source: &native::WGPUShaderTranslationSource,
+    source_kind: ShaderSourceKind,
+    target_kind: ShaderTargetKind,
+) -> Result<Vec<u8>, TranslationError> {
+    let code = make_slice(source.code as *const u8, source.codeSize);
+    let entry_point = OwnedLabel::new(source.entryPoint).into_inner();
+    let stage = map_shader_stage(source.stage);
+
+    let parse_error =
+        |message| TranslationError::new(native::WGPUShaderTranslationStatus_ParseError, message);
+    let code_as_str =
+        || std::str::from_utf8(code).map_err(|e| parse_error(format!("Invalid UTF-8: {}", e)));
+
+    let module = match source_kind {
+        ShaderSourceKind::Wgsl => {
+            naga::front::wgsl::parse_str(code_as_str()?).map_err(TranslationError::from_wgsl)?
+        }
+        ShaderSourceKind::SpirV => naga::front::spv::parse_u8_slice(code, &Default::default())
+            .map_err(|e| parse_error(format!("{:?}", e)))?,
+        ShaderSourceKind::Glsl => {
+            let entry_point = entry_point
+                .as_ref()
+                .ok_or_else(|| parse_error("GLSL input requires an entry point".to_string()))?;
+            let stage = stage
+                .ok_or_else(|| parse_error("GLSL input requires a shader stage".to_string()))?;
+            naga::front::glsl::parse_str(code_as_str()?, entry_point, stage, Default::default())
+                .map_err(|e| parse_error(format!("{:?}", e)))?
+        }
+    };
+
+    naga::proc::Validator::new()
+        .validate(&module)
+        .map_err(|e| {
+            TranslationError::new(
+                native::WGPUShaderTranslationStatus_ValidationError,
+                format!("{:?}", e),
+            )
+        })?;
+
+    let translation_error = |message| {
+        TranslationError::new(
+            native::WGPUShaderTranslationStatus_TranslationError,
+            message,
+        )
+    };
+
+    match target_kind {
+        ShaderTargetKind::SpirV => {
+            let capabilities = [naga::back::spv::Capability::Shader]
+                .iter()
+                .cloned()
+                .collect();
+            let words = naga::back::spv::write_vec(
+                &module,
+                naga::back::spv::WriterFlags::empty(),
+                capabilities,
+            )
+            .map_err(|e| translation_error(format!("{:?}", e)))?;
+            Ok(words
+                .iter()
+                .flat_map(|word| word.to_ne_bytes().to_vec())
+                .collect())
+        }
+        ShaderTargetKind::Glsl => {
+            let entry_point = entry_point.ok_or_else(|| {
+                translation_error("GLSL output requires an entry point".to_string())
+            })?;
+            let stage = stage.ok_or_else(|| {
+                translation_error("GLSL output requires a shader stage".to_string())
+            })?;
+            let options = naga::back::glsl::Options {
+                version: naga::back::glsl::Version::Desktop(450),
+                entry_point: (stage, entry_point),
+            };
+            let mut output = Vec::new();
+            naga::back::glsl::Writer::new(&mut output, &module, &options)
+                .and_then(|mut writer| writer.write())
+                .map_err(|e| translation_error(format!("{:?}", e)))?;
+            Ok(output)
+        }
+        ShaderTargetKind::Wgsl => Err(TranslationError::new(
+            native::WGPUShaderTranslationStatus_Unsupported,
+            "WGSL output is not supported by this version of naga".to_string(),
+        )),
+    }
+}
+
+fn map_shader_stage(value: native::WGPUShaderStage) -> Option<naga::ShaderStage> {
+    match value {
+        native::WGPUShaderStage_Vertex => Some(naga::ShaderStage::Vertex),
+        native::WGPUShaderStage_Fragment => Some(naga::ShaderStage::Fragment),
+        native::WGPUShaderStage_Compute => Some(naga::ShaderStage::Compute),
+        _ => None,
+    }
+}
+
+map_enum!(
+    map_shader_source_kind,
+    WGPUShaderSourceKind,
+    ShaderSourceKind,
+    "Unknown shader source kind",
+    WGSL:Wgsl,
+    SPIRV:SpirV,
+    GLSL:Glsl
+);
+map_enum!(
+    map_shader_target_kind,
+    WGPUShaderTargetKind,
+    ShaderTargetKind,
+    "Unknown shader target kind",
+    SPIRV:SpirV,
+    GLSL:Glsl,
+    WGSL:Wgsl
+);